@@ -0,0 +1,122 @@
+//! With the `embed-tables` feature, build the default solver's pruning tables with the library's
+//! own code and save them to `OUT_DIR`, where `pruning.rs` embeds them with `include_bytes!`. Only
+//! the modules the tables are built from are compiled here, and only with the feature.
+
+#[cfg(feature = "embed-tables")]
+use std::{env, fmt::Write, fs, path::Path};
+
+#[cfg(feature = "embed-tables")]
+#[path = "src/alg.rs"]
+mod alg;
+#[cfg(feature = "embed-tables")]
+#[path = "src/coord/mod.rs"]
+mod coord;
+#[cfg(feature = "embed-tables")]
+#[path = "src/cube.rs"]
+mod cube;
+#[cfg(feature = "embed-tables")]
+#[path = "src/face.rs"]
+mod face;
+#[cfg(feature = "embed-tables")]
+#[path = "src/math.rs"]
+mod math;
+#[cfg(feature = "embed-tables")]
+#[path = "src/metric.rs"]
+mod metric;
+#[cfg(feature = "embed-tables")]
+#[path = "src/mov.rs"]
+mod mov;
+#[cfg(feature = "embed-tables")]
+#[path = "src/notation.rs"]
+mod notation;
+#[cfg(feature = "embed-tables")]
+#[path = "src/perm.rs"]
+mod perm;
+#[cfg(feature = "embed-tables")]
+#[path = "src/piece.rs"]
+mod piece;
+#[cfg(feature = "embed-tables")]
+#[path = "src/pruning.rs"]
+mod pruning;
+#[cfg(feature = "embed-tables")]
+#[path = "src/sym.rs"]
+mod sym;
+#[cfg(feature = "embed-tables")]
+#[path = "src/sym48.rs"]
+mod sym48;
+#[cfg(feature = "embed-tables")]
+#[path = "src/table_file.rs"]
+mod table_file;
+
+#[cfg(feature = "embed-tables")]
+pub use alg::*;
+#[cfg(feature = "embed-tables")]
+pub use coord::*;
+#[cfg(feature = "embed-tables")]
+pub use cube::*;
+#[cfg(feature = "embed-tables")]
+pub use face::*;
+#[cfg(feature = "embed-tables")]
+use math::*;
+#[cfg(feature = "embed-tables")]
+pub use metric::*;
+#[cfg(feature = "embed-tables")]
+pub use mov::*;
+#[cfg(feature = "embed-tables")]
+pub use notation::*;
+#[cfg(feature = "embed-tables")]
+pub use perm::*;
+#[cfg(feature = "embed-tables")]
+pub use piece::*;
+#[cfg(feature = "embed-tables")]
+pub use pruning::*;
+#[cfg(feature = "embed-tables")]
+pub use sym::*;
+#[cfg(feature = "embed-tables")]
+pub use sym48::*;
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    println!("cargo::rerun-if-changed=src");
+    #[cfg(feature = "embed-tables")]
+    embed_tables();
+}
+
+/// Build the tables, write `tables.rs` listing them and tell the library they're there.
+#[cfg(feature = "embed-tables")]
+fn embed_tables() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+    let mut names = vec![];
+    embed::<CoordCO, CoordESlice, Bytes>(out_dir, Move::all(), &mut names);
+    embed::<CoordEO, CoordESlice, Bytes>(out_dir, Move::all(), &mut names);
+    embed::<CoordCP, CoordESliceEP, Bytes>(out_dir, Move::drud_moveset(), &mut names);
+    embed::<CoordEP, CoordESliceEP, Bytes>(out_dir, Move::drud_moveset(), &mut names);
+
+    let mut src = String::from("pub(crate) static TABLES: &[(&str, &[u8])] = &[\n");
+    for name in names {
+        writeln!(
+            src,
+            "    ({name:?}, include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{name}\"))),"
+        )
+        .unwrap();
+    }
+    src.push_str("];\n");
+    fs::write(out_dir.join("tables.rs"), src).unwrap();
+    println!("cargo::rustc-cfg=embedded_tables");
+}
+
+/// Build the HTM table for the moveset and save it to the output directory.
+#[cfg(feature = "embed-tables")]
+fn embed<R, C, S>(out_dir: &Path, moveset: &'static [Move], names: &mut Vec<String>)
+where
+    R: SymCoord,
+    C: Coord,
+    S: Storage,
+{
+    let name = PrunTable::<R, C, S>::file_name(moveset, Metric::Htm);
+    PrunTable::<R, C, S>::new(moveset)
+        .save(out_dir.join(&name), moveset, Metric::Htm)
+        .unwrap();
+    names.push(name);
+}
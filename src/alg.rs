@@ -0,0 +1,240 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::*;
+
+/// A sequence of moves.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Alg(Vec<Move>);
+
+impl Alg {
+    pub fn new(moves: Vec<Move>) -> Self {
+        Self(moves)
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.0
+    }
+
+    /// Number of moves in the alg.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Length of the alg in the given metric.
+    pub fn len_in(&self, metric: Metric) -> i32 {
+        metric.len(&self.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply the alg to the given cube.
+    pub fn apply(&self, c: &Cube) -> Cube {
+        self.0.iter().fold(c.clone(), |c, m| c.apply_move(*m))
+    }
+
+    /// The cube obtained by applying the alg to a solved cube.
+    pub fn to_cube(&self) -> Cube {
+        self.apply(&Cube::default())
+    }
+
+    /// The alg which undoes this one.
+    pub fn inverse(&self) -> Self {
+        self.0
+            .iter()
+            .rev()
+            .map(|m| {
+                let (f, n) = m.decompose();
+                Move::new(f, -n).unwrap()
+            })
+            .collect()
+    }
+
+    /// Mirror the alg through the plane perpendicular to the given axis, e.g. mirroring across
+    /// `Axis::RL` turns `R U` into `L' U'`.
+    pub fn mirror(&self, axis: Axis) -> Self {
+        self.0
+            .iter()
+            .map(|m| {
+                let (f, n) = m.decompose();
+                let f = if f.axis() == axis { f.opposite() } else { f };
+                Move::new(f, -n).unwrap()
+            })
+            .collect()
+    }
+
+    /// Conjugate each move of the alg by the symmetry, so that the resulting alg produces the
+    /// conjugate under `s` of the cube produced by this alg.
+    pub fn conjugate_by(&self, s: Sym) -> Self {
+        self.map_moves(|c| s.conj(c))
+    }
+
+    /// Map an alg solving the cube conjugated by `Sym::conj_axis` back to one solving the
+    /// original cube.
+    pub fn unconjugate_axis(&self, axis: Axis) -> Self {
+        let s = Sym::axis_conjugator(axis);
+        let s1 = s.inverse();
+        self.map_moves(|c| s1.compose(c).compose(&s))
+    }
+
+    /// Replace each move by the move producing the given conjugate of it.
+    fn map_moves<F>(&self, conj: F) -> Self
+    where
+        F: Fn(&Cube) -> Cube,
+    {
+        self.0
+            .iter()
+            .map(|m| {
+                let c = conj(&Cube::from(*m));
+                *Move::all().iter().find(|n| Cube::from(**n) == c).unwrap()
+            })
+            .collect()
+    }
+
+    /// Cancel and merge moves on the same face, including across a move on the opposite face,
+    /// and order each pair of moves on opposite faces in the canonical order used by the solver.
+    pub fn simplify(&self) -> Self {
+        let mut ret: Vec<Move> = vec![];
+        for m in &self.0 {
+            let n = ret.len();
+            let i = if n >= 1 && ret[n - 1].cancels_with(m) {
+                n - 1
+            } else if n >= 2 && ret[n - 1].commutes_with(m) && ret[n - 2].cancels_with(m) {
+                n - 2
+            } else {
+                ret.push(*m);
+                continue;
+            };
+            let (f, a) = ret[i].decompose();
+            let (_, b) = m.decompose();
+            match Move::new(f, a + b) {
+                Some(merged) => ret[i] = merged,
+                None => {
+                    ret.remove(i);
+                }
+            }
+        }
+        for i in 1..ret.len() {
+            if ret[i - 1].commutes_with(&ret[i]) && ret[i] < ret[i - 1] {
+                ret.swap(i - 1, i);
+            }
+        }
+        Self(ret)
+    }
+}
+
+impl From<Vec<Move>> for Alg {
+    fn from(moves: Vec<Move>) -> Self {
+        Self(moves)
+    }
+}
+
+impl FromIterator<Move> for Alg {
+    fn from_iter<T: IntoIterator<Item = Move>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl TryFrom<&str> for Alg {
+    type Error = String;
+
+    /// Parse a sequence of moves, see `parse_moves` for the supported notation.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        parse_moves(s).map(Self)
+    }
+}
+
+impl FromStr for Alg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl Display for Alg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let moves = self.0.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", moves.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn alg(s: &str) -> Alg {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let a = alg("R U2 R' D' F2 B L2");
+        assert_eq!(a.to_string(), "R U2 R' D' F2 B L2");
+        assert_eq!(alg(&a.to_string()), a);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let a = alg("R U R' U' F2 D' L");
+        assert_eq!(a.inverse(), alg("L' D F2 U R U' R'"));
+        assert!(a.inverse().apply(&a.to_cube()).is_solved());
+    }
+
+    #[test]
+    fn test_mirror() {
+        assert_eq!(alg("R U F2").mirror(Axis::RL), alg("L' U' F2"));
+        assert_eq!(alg("R U F2").mirror(Axis::UD), alg("R' D' F2"));
+        assert_eq!(alg("R U F'").mirror(Axis::FB), alg("R' U' B"));
+
+        // Mirroring twice is the identity, and mirroring across M matches the LR symmetry
+        let a = alg("R U R' U' F2 D' L");
+        assert_eq!(a.mirror(Axis::FB).mirror(Axis::FB), a);
+        assert_eq!(a.mirror(Axis::RL).to_cube(), Sym::UF2.conj(&a.to_cube()));
+    }
+
+    #[test]
+    fn test_conjugate_by() {
+        let a = alg("R U R' U' F2 D' L");
+        for coord in 0..16 {
+            let s = Sym::from_coord(coord);
+            let b = a.conjugate_by(s);
+            assert_eq!(b.to_cube(), s.conj(&a.to_cube()));
+        }
+        assert_eq!(a.conjugate_by(Sym::UR).conjugate_by(Sym::UL), a);
+    }
+
+    #[test]
+    fn test_unconjugate_axis() {
+        let a = alg("F B2 R U' L");
+        let conj = Sym::conj_axis(Axis::FB, &a.to_cube());
+        assert_eq!(conj, alg("U D2 R B' L").to_cube());
+        assert_eq!(alg("U D2 R B' L").unconjugate_axis(Axis::FB), a);
+
+        let a = alg("R L' F D2");
+        let conj = Sym::conj_axis(Axis::RL, &a.to_cube());
+        let sol = alg("R2 F' D U'");
+        assert!(sol.apply(&conj).is_solved());
+        assert!(
+            sol.unconjugate_axis(Axis::RL)
+                .apply(&a.to_cube())
+                .is_solved()
+        );
+    }
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(alg("R R").simplify(), alg("R2"));
+        assert_eq!(alg("R L R").simplify(), alg("R2 L"));
+        assert_eq!(alg("L R").simplify(), alg("R L"));
+        assert_eq!(alg("R U U' R'").simplify(), alg(""));
+        assert_eq!(alg("U D R R' U'").simplify(), alg("D"));
+        assert_eq!(alg("R L R L'").simplify(), alg("R2"));
+        assert_eq!(alg("F D U' F'").simplify(), alg("F U' D F'"));
+
+        let a = alg("R U2 U2 R' L R D D' L2 B B2 B");
+        assert_eq!(a.simplify(), alg("R L'"));
+        assert_eq!(a.simplify().to_cube(), a.to_cube());
+    }
+}
@@ -12,10 +12,10 @@ impl Coord for CoordCO {
         c.co.coord()
     }
 
-    fn rep(c: usize) -> Cube {
+    fn set(c: &Cube, coord: usize) -> Cube {
         Cube {
-            co: CO::from_coord(c),
-            ..Cube::default()
+            co: CO::from_coord(coord),
+            ..*c
         }
     }
 
@@ -23,6 +23,17 @@ impl Coord for CoordCO {
         s.conj_corners(c)
     }
 
+    const INVERTIBLE_WITH_CP: bool = true;
+}
+
+impl SymCoord for CoordCO {
+    fn rep(c: usize) -> Cube {
+        Cube {
+            co: CO::from_coord(c),
+            ..Cube::default()
+        }
+    }
+
     fn syms() -> &'static [Sym] {
         &[
             Sym::UF,
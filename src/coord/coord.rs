@@ -1,6 +1,7 @@
-use std::marker::PhantomData;
+use std::{io, marker::PhantomData, path::Path};
 
 use crate::*;
+use table_file::*;
 
 /// Description of a coordinate which maps to a (right) coset of the cube.
 pub trait Coord {
@@ -14,46 +15,157 @@ pub trait Coord {
     /// [0, N_VALUES).
     fn index(c: &Cube) -> usize;
 
-    /// Given a coordinate, produce a cube which has that coordinate when `index` is
-    /// called on it.
-    fn rep(c: usize) -> Cube;
+    /// Modify the pieces of the cube which the coordinate depends on, so that it has the given
+    /// coordinate when `index` is called on it. Pieces which the coordinate does not depend on
+    /// are left as they are, so that coordinates on disjoint pieces can be set independently.
+    fn set(c: &Cube, coord: usize) -> Cube;
 
     // Apply a symmetry conjugation to the cube which transforms the coordinate appropriately,
     // for example conjugating corners for a `CO` coordinate.
     fn conj(c: &Cube, s: Sym) -> Cube;
 
-    /// Static listing of symmetries which are applicable to the coordinate, i.e. symmetries
-    /// `S` which have the the property: if `x`, `y` are cubes with the same coordinate, then
-    /// the conjugates of `x`, `y` under `S` also have the same coordinate.
+    /// Symmetries which are applicable to the coordinate, i.e. symmetries `S` which have the
+    /// property: if `x`, `y` are cubes with the same coordinate, then the conjugates of `x`, `y`
+    /// under `S` also have the same coordinate. A table pairing the coordinate with a
+    /// symmetry-reduced one is only reduced by these.
+    const SYMS: &'static [Sym] = Sym::all();
+
+    /// Whether the coordinate of the inverse of a cube is determined by the coordinate of the
+    /// cube, so that tables using the coordinate can be reduced by inversion, see `Antisym`.
+    /// Coordinates which assume that the E-slice pieces stay within the E-slice only hold this
+    /// in DR, so tables reduced by inversion with them must use a moveset which keeps DR.
+    const INVERTIBLE: bool = false;
+
+    /// Whether the coordinate of the inverse of a cube is determined by the coordinate of the
+    /// cube together with the permutation of the corners, like CO.
+    const INVERTIBLE_WITH_CP: bool = false;
+
+    /// Whether the coordinate determines the permutation of the corners.
+    const HAS_CP: bool = false;
+}
+
+/// A coordinate which can be reduced by symmetry.
+pub trait SymCoord: Coord {
+    /// Given a coordinate, produce a cube which has that coordinate when `index` is
+    /// called on it.
+    fn rep(c: usize) -> Cube;
+
+    /// Static listing of symmetries which the coordinate is reduced by, some of `SYMS`.
     fn syms() -> &'static [Sym];
+
+    /// Whether the coordinate is reduced by inversion as well as by its symmetries.
+    const ANTISYM: bool = false;
+}
+
+/// The coordinate `C` reduced by inversion as well as by its symmetries, which roughly halves
+/// the number of classes. Only tables where a cube and its inverse are the same distance away
+/// can use this, and the coordinates of the inverse must be determined by those of the cube.
+pub struct Antisym<C>(PhantomData<fn() -> C>);
+
+impl<C> Coord for Antisym<C>
+where
+    C: SymCoord,
+{
+    const NAME: &'static str = C::NAME;
+    const N_VALUES: usize = C::N_VALUES;
+    const SYMS: &'static [Sym] = C::SYMS;
+    const INVERTIBLE: bool = C::INVERTIBLE;
+    const INVERTIBLE_WITH_CP: bool = C::INVERTIBLE_WITH_CP;
+    const HAS_CP: bool = C::HAS_CP;
+
+    fn index(c: &Cube) -> usize {
+        C::index(c)
+    }
+
+    fn set(c: &Cube, coord: usize) -> Cube {
+        C::set(c, coord)
+    }
+
+    fn conj(c: &Cube, s: Sym) -> Cube {
+        C::conj(c, s)
+    }
+}
+
+impl<C> SymCoord for Antisym<C>
+where
+    C: SymCoord,
+{
+    const ANTISYM: bool = true;
+
+    fn rep(c: usize) -> Cube {
+        C::rep(c)
+    }
+
+    fn syms() -> &'static [Sym] {
+        C::syms()
+    }
 }
 
 /// Symmetry information about a coordinate `C`.
 pub struct SymTable<C>
 where
-    C: Coord,
+    C: SymCoord,
 {
     /// Symmetries which bring the cube with the given coordinate back
     /// to the canonical representative.
     conj: Vec<Sym>,
 
+    /// Whether the cube with the given coordinate is inverted before the symmetry is applied,
+    /// empty unless the coordinate is reduced by inversion.
+    inv: Vec<bool>,
+
     /// Conjugacy class of each coordinate.
     cls: Vec<usize>,
 
-    /// Self-symmetries of each conjugacy class, encoded as a bitset.
-    ssym: Vec<u16>,
+    /// Self-symmetries of each conjugacy class, encoded as a bitset, followed by the
+    /// self-antisymmetries (symmetries applied after inversion).
+    ssym: Vec<u32>,
+
+    /// Representative coordinate of each conjugacy class.
+    reps: Vec<usize>,
 
-    _c: PhantomData<C>,
+    _c: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for SymTable<C>
+where
+    C: SymCoord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<C> SymTable<C>
 where
-    C: Coord,
+    C: SymCoord,
 {
     pub fn new() -> Self {
-        let mut conj: Vec<Option<Sym>> = vec![None; C::N_VALUES];
+        Self::with_syms(C::syms())
+    }
+
+    /// Table for reducing the coordinate by only some of its symmetries, which must form a group.
+    /// Such a table is only saved as part of a pruning table, whose header records the symmetries.
+    pub(crate) fn with_syms(syms: &[Sym]) -> Self {
+        assert!(
+            !C::ANTISYM || C::INVERTIBLE,
+            "{} cannot be reduced by inversion",
+            C::NAME
+        );
+        let contains = |s: Sym| syms.iter().any(|t| t.coord() == s.coord());
+        assert!(
+            contains(Sym::UF)
+                && syms
+                    .iter()
+                    .all(|a| syms.iter().all(|b| contains(a.compose(*b)))),
+            "symmetries of {} do not form a group",
+            C::NAME
+        );
+        let inversions: &[bool] = if C::ANTISYM { &[false, true] } else { &[false] };
+        let mut conj: Vec<Option<(Sym, bool)>> = vec![None; C::N_VALUES];
         let mut cls: Vec<usize> = vec![0; C::N_VALUES];
-        let mut ssym: Vec<u16> = vec![];
+        let mut ssym: Vec<u32> = vec![];
+        let mut reps: Vec<usize> = vec![];
 
         let mut clsno = 0;
         for a_coord in 0..C::N_VALUES {
@@ -61,29 +173,126 @@ where
             if conj[a_coord].is_some() {
                 continue;
             }
-            let a = C::rep(a_coord);
-            for sym in C::syms() {
-                let b = C::conj(&a, *sym);
-                let b_coord = C::index(&b);
-                conj[b_coord] = Some(sym.inverse());
-                cls[b_coord] = clsno;
-                if a_coord == b_coord {
-                    s |= 1 << sym.coord();
+            for inv in inversions {
+                let a = C::rep(a_coord);
+                let a = if *inv { a.inverse() } else { a };
+                for sym in syms {
+                    let b = C::conj(&a, *sym);
+                    let b_coord = C::index(&b);
+                    conj[b_coord] = Some((sym.inverse(), *inv));
+                    cls[b_coord] = clsno;
+                    if a_coord == b_coord {
+                        s |= 1 << (sym.coord() + 16 * *inv as usize);
+                    }
                 }
             }
             clsno += 1;
             ssym.push(s);
+            reps.push(a_coord);
         }
 
-        let conj = conj.into_iter().map(Option::unwrap).collect();
+        let (conj, inv): (Vec<_>, Vec<_>) = conj.into_iter().map(Option::unwrap).unzip();
         Self {
             conj,
+            inv: if C::ANTISYM { inv } else { vec![] },
             cls,
             ssym,
+            reps,
             _c: PhantomData,
         }
     }
 
+    /// Write the table to a file, see `load`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_table(path.as_ref(), &Self::header(), &self.to_bytes())
+    }
+
+    /// Read a table written by `save`, failing if the file was written for a different
+    /// coordinate or symmetries, by another version of the format, or is corrupt.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&read_table(path.as_ref(), &Self::header())?)
+    }
+
+    /// Load the table from the file, or generate it and try to save it there if it can't be
+    /// loaded.
+    pub fn load_or_generate(path: impl AsRef<Path>) -> Self {
+        Self::load(&path).unwrap_or_else(|_| {
+            let ret = Self::new();
+            let _ = ret.save(&path);
+            ret
+        })
+    }
+
+    fn header() -> TableHeader {
+        let mut syms: Vec<u8> = C::syms().iter().map(|s| s.coord() as u8).collect();
+        syms.push(C::ANTISYM as u8);
+        TableHeader {
+            names: vec![C::NAME, "SymTable"],
+            n_values: vec![C::N_VALUES],
+            fingerprint: fnv1a(&syms),
+        }
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut ret = vec![];
+        ret.extend(
+            (0..C::N_VALUES).map(|i| self.conj[i].coord() as u8 | (self.inverts(i) as u8) << 4),
+        );
+        for cls in &self.cls {
+            ret.extend((*cls as u32).to_le_bytes());
+        }
+        ret.extend((self.n_conj_classes() as u32).to_le_bytes());
+        for s in &self.ssym {
+            ret.extend(s.to_le_bytes());
+        }
+        for r in &self.reps {
+            ret.extend((*r as u32).to_le_bytes());
+        }
+        ret
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = Reader::new(bytes);
+        let (conj, inv): (Vec<_>, Vec<_>) = (0..C::N_VALUES)
+            .map(|_| match r.u8()? {
+                s if s < 16 || C::ANTISYM && s < 32 => {
+                    Ok((Sym::from_coord(s as usize % 16), s >= 16))
+                }
+                _ => Err(invalid("invalid symmetry")),
+            })
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let cls = (0..C::N_VALUES)
+            .map(|_| Ok(r.u32()? as usize))
+            .collect::<io::Result<Vec<_>>>()?;
+        let n = r.u32()? as usize;
+        let ssym = (0..n).map(|_| r.u32()).collect::<io::Result<Vec<_>>>()?;
+        let reps = (0..n)
+            .map(|_| Ok(r.u32()? as usize))
+            .collect::<io::Result<Vec<_>>>()?;
+        if !r.is_empty() || cls.iter().any(|c| *c >= n) || reps.iter().any(|c| *c >= C::N_VALUES) {
+            return Err(invalid("invalid symmetry table"));
+        }
+        Ok(Self {
+            conj,
+            inv: if C::ANTISYM { inv } else { vec![] },
+            cls,
+            ssym,
+            reps,
+            _c: PhantomData,
+        })
+    }
+
+    /// Memory used by the table in bytes.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        self.conj.len() * size_of::<Sym>()
+            + self.inv.len() * size_of::<bool>()
+            + (self.cls.len() + self.reps.len()) * size_of::<usize>()
+            + self.ssym.len() * size_of::<u32>()
+    }
+
     /// Number of conjugacy classes for the coordinate.
     pub fn n_conj_classes(&self) -> usize {
         self.ssym.len()
@@ -94,12 +303,28 @@ where
         self.conj[coord]
     }
 
+    /// Whether the cube with the coordinate is inverted before applying `canonical_conj` to
+    /// bring it to its class representative.
+    pub fn inverts(&self, coord: usize) -> bool {
+        C::ANTISYM && self.inv[coord]
+    }
+
     /// Canonicalize the given cube, applying a transformation so that coordinate is the
     /// representative of its conjugacy class.
     pub fn canonicalize(&self, c: &Cube) -> Cube {
         let coord = C::index(c);
         let sym = self.canonical_conj(coord);
-        sym.conj(c)
+        if self.inverts(coord) {
+            sym.conj(&c.inverse())
+        } else {
+            sym.conj(c)
+        }
+    }
+
+    /// Representative coordinate of the conjugacy class, which `canonicalize` brings every
+    /// coordinate in the class to.
+    pub fn class_rep(&self, cls: usize) -> usize {
+        self.reps[cls]
     }
 
     /// Conjugacy class of the given coordinate.
@@ -107,8 +332,9 @@ where
         self.cls[coord]
     }
 
-    /// An iterator over self-symmetries of the conjugacy class.
-    pub fn self_syms(&self, c: &Cube) -> impl Iterator<Item = Sym> {
+    /// An iterator over self-symmetries of the conjugacy class, along with whether the cube is
+    /// inverted before the symmetry is applied.
+    pub fn self_syms(&self, c: &Cube) -> impl Iterator<Item = (Sym, bool)> {
         let cls = self.conj_class(C::index(c));
         let mut n = self.ssym[cls];
         std::iter::from_fn(move || {
@@ -117,8 +343,127 @@ where
             } else {
                 let pos = n.trailing_zeros();
                 n &= n - 1;
-                Some(Sym::from_coord(pos as usize))
+                Some((Sym::from_coord(pos as usize % 16), pos >= 16))
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn check_set<C: Coord>(base: &Cube) {
+        for coord in (0..C::N_VALUES).step_by(C::N_VALUES / 97 + 1) {
+            let c = C::set(base, coord);
+            assert_eq!(C::index(&c), coord, "{}", C::NAME);
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        // Setting a coordinate on a phase 2 cube leaves the others alone
+        let base = Alg::try_from("U R2 D' F2 L2 U2 B2").unwrap().to_cube();
+        check_set::<CoordCO>(&base);
+        check_set::<CoordEO>(&base);
+        check_set::<CoordESlice>(&base);
+        check_set::<CoordCP>(&base);
+        check_set::<CoordEP>(&base);
+        check_set::<CoordESliceEP>(&base);
+        check_set::<CoordFlipSlice>(&base);
+
+        let c = CoordESliceEP::set(&base, 5);
+        assert_eq!(CoordEP::index(&c), CoordEP::index(&base));
+        let c = CoordCO::set(&base, 5);
+        assert_eq!(CoordCP::index(&c), CoordCP::index(&base));
+    }
+
+    fn check_syms<C: Coord>(bases: &[Cube]) {
+        for coord in (0..C::N_VALUES).step_by(C::N_VALUES / 97 + 1) {
+            for s in C::SYMS {
+                let mut coords = bases
+                    .iter()
+                    .map(|b| C::index(&C::conj(&C::set(b, coord), *s)));
+                let first = coords.next().unwrap();
+                assert!(coords.all(|x| x == first), "{} {}", C::NAME, s.coord());
+            }
+        }
+    }
+
+    #[test]
+    fn test_syms() {
+        // Cubes with the same coordinate still agree on it after any of the declared symmetries
+        let dr = ["U R2 D' F2 L2 U2 B2", "R2 U' F2 D L2 B2 U2 R2 D'"]
+            .map(|a| Alg::try_from(a).unwrap().to_cube());
+        let any = ["R U F' L2 D B' R' F2 U' L", "F R' D2 B L' U F2"]
+            .map(|a| Alg::try_from(a).unwrap().to_cube());
+        let all = [dr.as_slice(), any.as_slice()].concat();
+        check_syms::<CoordCO>(&all);
+        check_syms::<CoordEO>(&all);
+        check_syms::<CoordESlice>(&all);
+        check_syms::<CoordCP>(&all);
+        check_syms::<CoordFlipSlice>(&all);
+        check_syms::<CoordEP>(&dr);
+        check_syms::<CoordESliceEP>(&dr);
+
+        // Coordinates are only reduced by symmetries they are compatible with
+        fn reduced_by_declared<C: SymCoord>() -> bool {
+            C::syms()
+                .iter()
+                .all(|s| C::SYMS.iter().any(|t| t.coord() == s.coord()))
+        }
+        assert!(reduced_by_declared::<CoordCO>());
+        assert!(reduced_by_declared::<CoordEO>());
+        assert!(reduced_by_declared::<CoordCP>());
+        assert!(reduced_by_declared::<CoordEP>());
+        assert!(reduced_by_declared::<CoordFlipSlice>());
+    }
+
+    #[test]
+    fn test_flip_slice_classes() {
+        let table = SymTable::<CoordFlipSlice>::new();
+        assert_eq!(table.n_conj_classes(), 64430);
+    }
+
+    #[test]
+    fn test_antisym() {
+        let sym = SymTable::<CoordCP>::new();
+        let antisym = SymTable::<Antisym<CoordCP>>::new();
+        assert!(antisym.n_conj_classes() < sym.n_conj_classes());
+        assert!(2 * antisym.n_conj_classes() > sym.n_conj_classes());
+
+        // A cube and its inverse are in the same class
+        let mut c = Cube::default();
+        for m in Alg::try_from("R U F' L2 D B' R' F2 U' L").unwrap().moves() {
+            c = c.apply_move(*m);
+            let a = CoordCP::index(&antisym.canonicalize(&c));
+            let b = CoordCP::index(&antisym.canonicalize(&c.inverse()));
+            assert_eq!(a, b);
+        }
+
+        let bytes = antisym.to_bytes();
+        assert_eq!(
+            SymTable::<Antisym<CoordCP>>::from_bytes(&bytes)
+                .unwrap()
+                .to_bytes(),
+            bytes
+        );
+        assert!(SymTable::<CoordCP>::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("sym_eo_{}.bin", std::process::id()));
+        let table = SymTable::<CoordEO>::new();
+        table.save(&path).unwrap();
+        let loaded = SymTable::<CoordEO>::load(&path).unwrap();
+        assert_eq!(loaded.to_bytes(), table.to_bytes());
+
+        // A table for another coordinate is not loaded from the file
+        assert!(SymTable::<CoordCO>::load(&path).is_err());
+        let table = SymTable::<CoordCO>::load_or_generate(&path);
+        assert_eq!(table.to_bytes(), SymTable::<CoordCO>::new().to_bytes());
+        assert!(SymTable::<CoordCO>::load(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -12,10 +12,10 @@ impl Coord for CoordCP {
         c.cp.index()
     }
 
-    fn rep(c: usize) -> Cube {
+    fn set(c: &Cube, coord: usize) -> Cube {
         Cube {
-            cp: Perm::<8>::from_index(c),
-            ..Cube::default()
+            cp: Perm::<8>::from_index(coord),
+            ..*c
         }
     }
 
@@ -23,6 +23,18 @@ impl Coord for CoordCP {
         s.conj_corners(c)
     }
 
+    const INVERTIBLE: bool = true;
+    const HAS_CP: bool = true;
+}
+
+impl SymCoord for CoordCP {
+    fn rep(c: usize) -> Cube {
+        Cube {
+            cp: Perm::<8>::from_index(c),
+            ..Cube::default()
+        }
+    }
+
     fn syms() -> &'static [Sym] {
         &[
             Sym::UF,
@@ -12,10 +12,10 @@ impl Coord for CoordEO {
         c.eo.coord()
     }
 
-    fn rep(c: usize) -> Cube {
+    fn set(c: &Cube, coord: usize) -> Cube {
         Cube {
-            eo: EO::from_coord(c),
-            ..Cube::default()
+            eo: EO::from_coord(coord),
+            ..*c
         }
     }
 
@@ -23,16 +23,29 @@ impl Coord for CoordEO {
         s.conj_edges(c)
     }
 
+    // The symmetries with a y rotation change the axis that the E-slice edges are oriented
+    // against, so EO alone is only preserved by the ones keeping the FB axis
+    const SYMS: &'static [Sym] = &[
+        Sym::UF,
+        Sym::UB,
+        Sym::DF,
+        Sym::DB,
+        Sym::UF2,
+        Sym::UB2,
+        Sym::DF2,
+        Sym::DB2,
+    ];
+}
+
+impl SymCoord for CoordEO {
+    fn rep(c: usize) -> Cube {
+        Cube {
+            eo: EO::from_coord(c),
+            ..Cube::default()
+        }
+    }
+
     fn syms() -> &'static [Sym] {
-        &[
-            Sym::UF,
-            Sym::UB,
-            Sym::DF,
-            Sym::DB,
-            Sym::UF2,
-            Sym::UB2,
-            Sym::DF2,
-            Sym::DB2,
-        ]
+        Self::SYMS
     }
 }
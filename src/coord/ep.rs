@@ -14,6 +14,33 @@ impl Coord for CoordEP {
             .index()
     }
 
+    fn set(c: &Cube, coord: usize) -> Cube {
+        // The E-slice pieces are assumed to stay within the E-slice, as they do in phase 2
+        use Edge::*;
+        let ep = Perm::<8>::from_index(coord);
+        let mut dests = [0; 12];
+        for e in Edge::all() {
+            let i = e.coord();
+            if *e == FL || *e == FR || *e == BL || *e == BR {
+                dests[i] = c.ep.dest(i);
+            } else {
+                dests[i] = ep.dest(i);
+            }
+        }
+        Cube {
+            ep: Perm::from_dests(&dests),
+            ..*c
+        }
+    }
+
+    fn conj(c: &Cube, s: Sym) -> Cube {
+        s.conj_edges(c)
+    }
+
+    const INVERTIBLE: bool = true;
+}
+
+impl SymCoord for CoordEP {
     fn rep(c: usize) -> Cube {
         use Edge::*;
         let ep = Perm::<8>::from_index(c);
@@ -32,10 +59,6 @@ impl Coord for CoordEP {
         }
     }
 
-    fn conj(c: &Cube, s: Sym) -> Cube {
-        s.conj_edges(c)
-    }
-
     fn syms() -> &'static [Sym] {
         &[
             Sym::UF,
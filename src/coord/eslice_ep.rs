@@ -13,20 +13,27 @@ impl Coord for CoordESliceEP {
         c.ep.mask(&[FL, FR, BL, BR].map(|e| e.coord())).index()
     }
 
-    fn rep(_c: usize) -> Cube {
-        // Not needed since we never reduce this coordinate by symmetry
-        // TODO: do not require this in `Coord` trait, only an extension of it
-        Cube::default()
+    fn set(c: &Cube, coord: usize) -> Cube {
+        // The E-slice pieces are assumed to stay within the E-slice, as they do in phase 2
+        use Edge::*;
+        let slice = [FL, FR, BL, BR].map(|e| e.coord());
+        let p = Perm::<4>::from_index(coord);
+        let mut dests = [0; 12];
+        for (i, dest) in dests.iter_mut().enumerate() {
+            *dest = c.ep.dest(i);
+        }
+        for (i, e) in slice.iter().enumerate() {
+            dests[*e] = slice[p.dest(i)];
+        }
+        Cube {
+            ep: Perm::from_dests(&dests),
+            ..*c
+        }
     }
 
     fn conj(c: &Cube, s: Sym) -> Cube {
         s.conj_edges(c)
     }
 
-    fn syms() -> &'static [Sym] {
-        // Not needed here, but eventually we'll want to take the intersection of symmetries
-        // between the reduced coordinate and the non-reduced one -- so maybe we can't lift
-        // this into another trait like `rep`
-        &[]
-    }
+    const INVERTIBLE: bool = true;
 }
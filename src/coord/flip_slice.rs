@@ -0,0 +1,53 @@
+use crate::coord::*;
+use crate::*;
+
+/// Coordinate combining EO with the locations of the E-slice pieces [0..2^11 * C(12, 4)).
+///
+/// EO alone is not preserved by the symmetries with a y rotation, since they change the axis
+/// that E-slice edges are oriented against, but the E-slice locations determine the change.
+pub struct CoordFlipSlice;
+
+impl Coord for CoordFlipSlice {
+    const NAME: &'static str = "CoordFlipSlice";
+    const N_VALUES: usize = CoordEO::N_VALUES * CoordESlice::N_VALUES;
+
+    fn index(c: &Cube) -> usize {
+        CoordEO::index(c) * CoordESlice::N_VALUES + CoordESlice::index(c)
+    }
+
+    fn set(c: &Cube, coord: usize) -> Cube {
+        let c = CoordEO::set(c, coord / CoordESlice::N_VALUES);
+        CoordESlice::set(&c, coord % CoordESlice::N_VALUES)
+    }
+
+    fn conj(c: &Cube, s: Sym) -> Cube {
+        s.conj_edges(c)
+    }
+}
+
+impl SymCoord for CoordFlipSlice {
+    fn rep(c: usize) -> Cube {
+        Self::set(&Cube::default(), c)
+    }
+
+    fn syms() -> &'static [Sym] {
+        &[
+            Sym::UF,
+            Sym::UR,
+            Sym::UL,
+            Sym::UB,
+            Sym::DF,
+            Sym::DR,
+            Sym::DL,
+            Sym::DB,
+            Sym::UF2,
+            Sym::UR2,
+            Sym::UL2,
+            Sym::UB2,
+            Sym::DF2,
+            Sym::DR2,
+            Sym::DL2,
+            Sym::DB2,
+        ]
+    }
+}
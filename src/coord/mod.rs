@@ -1,10 +1,12 @@
 mod co;
+#[allow(clippy::module_inception)]
 mod coord;
 mod cp;
 mod eo;
 mod ep;
 mod eslice;
 mod eslice_ep;
+mod flip_slice;
 
 pub use co::*;
 pub use coord::*;
@@ -13,3 +15,4 @@ pub use eo::*;
 pub use ep::*;
 pub use eslice::*;
 pub use eslice_ep::*;
+pub use flip_slice::*;
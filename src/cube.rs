@@ -1,4 +1,7 @@
-use std::{fmt::Debug, num::Wrapping};
+use std::{
+    fmt::{Debug, Display},
+    num::Wrapping,
+};
 
 use crate::*;
 
@@ -7,7 +10,7 @@ use crate::*;
 /// - CO=00 means correct CO relative to the axis
 /// - CO=01 means a clockwise twist is needed to correct CO relative to the axis
 /// - CO=10 means a counter-clockwise twist is needed to correct CO relative to the axis
-#[derive(Default, Copy, Clone, Eq, PartialEq)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct CO(pub u16);
 
 impl CO {
@@ -47,6 +50,11 @@ impl CO {
         Self(((Wrapping(self.0) + Wrapping(c.0)) - m - (m >> 1)).0)
     }
 
+    /// The twist of the corner at the given slot.
+    pub fn twist(&self, slot: Corner) -> u8 {
+        ((self.0 >> (slot.coord() * 2)) & 3) as u8
+    }
+
     pub fn inverse(&self) -> Self {
         CO((self.0 & 0x5555).wrapping_shl(1) | ((self.0 & 0xaaaa) >> 1))
     }
@@ -90,7 +98,7 @@ impl Debug for CO {
 
 // EO encodes edge orientation at each edge slot (1 bit * 12)
 // Storage: (1 bit * 12)
-#[derive(Default, Copy, Clone, Eq, PartialEq)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct EO(pub u16);
 
 impl EO {
@@ -155,7 +163,37 @@ impl Debug for EO {
     }
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
+/// Reasons a cube state cannot be reached from the solved cube.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unsolvable {
+    /// The edge or corner permutation is not a permutation.
+    InvalidPermutation,
+    /// The corner at the slot has an out-of-range twist.
+    InvalidTwist(Corner),
+    /// Edge and corner permutations have different parities, i.e. two pieces are swapped.
+    ParityMismatch,
+    /// The corner twists do not sum to 0 mod 3.
+    CornerTwist,
+    /// An odd number of edges are flipped.
+    EdgeFlip,
+}
+
+impl Display for Unsolvable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Unsolvable::*;
+        match self {
+            InvalidPermutation => write!(f, "pieces do not form a permutation"),
+            InvalidTwist(k) => write!(f, "corner at {k} has an invalid twist"),
+            ParityMismatch => write!(f, "edge and corner permutation parities differ"),
+            CornerTwist => write!(f, "corner twist is not 0 mod 3"),
+            EdgeFlip => write!(f, "an odd number of edges are flipped"),
+        }
+    }
+}
+
+impl std::error::Error for Unsolvable {}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Cube {
     // Edge orientation relative to FB-axis
     pub eo: EO,
@@ -190,6 +228,12 @@ impl Cube {
         Edge::from_coord(coord)
     }
 
+    /// The corner at the given slot.
+    pub fn corner_at(&self, slot: Corner) -> Corner {
+        let coord = self.cp.source(slot.coord());
+        Corner::from_coord(coord)
+    }
+
     pub fn inverse_edges(&self) -> Self {
         let ep = self.ep.inverse();
         let eo = self.eo.inverse().swizzle(ep);
@@ -221,6 +265,31 @@ impl Cube {
         }
     }
 
+    /// Check that the cube can be solved, reporting the first invariant which is violated.
+    pub fn validate(&self) -> Result<(), Unsolvable> {
+        if !self.ep.is_valid() || !self.cp.is_valid() {
+            return Err(Unsolvable::InvalidPermutation);
+        }
+        if self.ep.parity() != self.cp.parity() {
+            return Err(Unsolvable::ParityMismatch);
+        }
+        let mut twist = 0;
+        for k in Corner::all() {
+            let t = self.co.twist(*k);
+            if t >= 3 {
+                return Err(Unsolvable::InvalidTwist(*k));
+            }
+            twist += t;
+        }
+        if twist % 3 != 0 {
+            return Err(Unsolvable::CornerTwist);
+        }
+        if (self.eo.0 & 0xfff).count_ones() & 1 != 0 {
+            return Err(Unsolvable::EdgeFlip);
+        }
+        Ok(())
+    }
+
     pub fn is_eofb(&self) -> bool {
         self.eo.0 == 0
     }
@@ -295,6 +364,37 @@ impl Cube {
     }
 }
 
+impl Cube {
+    /// Every symmetry which conjugates the cube onto itself, and every antisymmetry which
+    /// conjugates it onto its inverse. The identity is always a symmetry.
+    pub fn symmetries(&self) -> SymSet {
+        let inv = self.inverse();
+        let mut ret = SymSet::default();
+        for s in Sym48::all() {
+            let c = s.conj(self);
+            if c == *self {
+                ret.insert(s);
+            }
+            if c == inv {
+                ret.insert_antisym(s);
+            }
+        }
+        ret
+    }
+
+    /// The lexicographically smallest conjugate of the cube by the symmetries in the set, along
+    /// with the symmetry which conjugates the cube onto it. Antisymmetries in the set are ignored.
+    /// The symmetry is a `Sym48`, since the set may hold any of the 48, and `Sym48::to_drud`
+    /// gives the `Sym` when the set only has DRUD-preserving symmetries.
+    pub fn canonical_under(&self, syms: SymSet) -> (Cube, Sym48) {
+        let key = |c: &Cube| (c.eo.0, c.co.0, c.ep.repr(), c.cp.repr());
+        syms.syms()
+            .map(|s| (s.conj(self), s))
+            .min_by_key(|(c, _)| key(c))
+            .unwrap_or((self.clone(), Sym48::from_coord(0)))
+    }
+}
+
 impl From<Move> for Cube {
     fn from(m: Move) -> Self {
         use Move::*;
@@ -369,6 +469,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(non_snake_case, unused_variables)]
     fn test_apply_moves() {
         let R: Cube = Move::R.into();
         let U: Cube = Move::U.into();
@@ -393,4 +494,110 @@ mod tests {
             .unwrap();
         assert_eq!(jperm.compose(&jperm), Cube::default());
     }
+
+    #[test]
+    fn test_validate() {
+        use Corner::*;
+        use Edge::*;
+        let c = Alg::try_from("R U R' U' F' L2 D B'").unwrap().to_cube();
+        assert_eq!(c.validate(), Ok(()));
+
+        let out_of_range = Cube::from_repr(
+            0,
+            0,
+            Perm::<12>::new().repr() | 0xf,
+            Perm::<8>::new().repr(),
+        );
+        assert_eq!(out_of_range.validate(), Err(Unsolvable::InvalidPermutation));
+
+        let flip = c.compose(&Cube {
+            eo: EO::from_bad_edges(&[UF]),
+            ..Cube::default()
+        });
+        assert_eq!(flip.validate(), Err(Unsolvable::EdgeFlip));
+
+        let twist = c.compose(&Cube {
+            co: CO::from_assoc(&[(UFR, 1)]),
+            ..Cube::default()
+        });
+        assert_eq!(twist.validate(), Err(Unsolvable::CornerTwist));
+
+        let swap = c.compose(&Cube {
+            ep: Perm::<12>::from_swap(UF.coord(), UB.coord()),
+            ..Cube::default()
+        });
+        assert_eq!(swap.validate(), Err(Unsolvable::ParityMismatch));
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let sym = |alg: &str| Alg::try_from(alg).unwrap().to_cube().symmetries();
+
+        // Solved, superflip and checkerboard are fixed by every symmetry and are their own inverse
+        let full = SymSet::all_with_antisyms();
+        assert_eq!(sym(""), full);
+        assert_eq!(
+            sym("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2"),
+            full
+        );
+        assert_eq!(sym("U2 D2 F2 B2 R2 L2"), full);
+        assert_eq!(full.class(), Some(SymClass::Oh));
+        assert_eq!(sym("U2 D2").class(), Some(SymClass::D4h));
+
+        // Cube-in-cube is fixed by the rotations about a corner diagonal, alone or followed by the
+        // inversion through the centre
+        let cube_in_cube = sym("F L F U' R U F2 L2 U' L' B D' B' L2 U");
+        assert_eq!(cube_in_cube.syms().count(), 6);
+        assert_eq!(cube_in_cube.syms().filter(|s| s.is_mirror()).count(), 3);
+        assert_eq!(cube_in_cube.antisyms().count(), 6);
+        assert_eq!(cube_in_cube.class(), Some(SymClass::S6));
+        for a in cube_in_cube.syms() {
+            for b in cube_in_cube.syms() {
+                assert!(cube_in_cube.contains(a.compose(b)));
+            }
+        }
+
+        // The sexy move is only taken to its inverse, by the half turn swapping U and R
+        let sexy = sym("R U R' U'");
+        assert_eq!(sexy.len(), 2);
+        assert!(sexy.is_chiral());
+        assert_eq!(sexy.class(), Some(SymClass::C1));
+        let s = sexy.antisyms().next().unwrap();
+        assert_eq!(s.conj(&Cube::from(Move::R)), Cube::from(Move::U));
+    }
+
+    #[test]
+    fn test_canonical_under() {
+        let c = Alg::try_from("R U2 F' L D B2 R' U").unwrap().to_cube();
+        let (canonical, s) = c.canonical_under(SymSet::all());
+        assert_eq!(s.conj(&c), canonical);
+        for t in Sym48::all() {
+            assert_eq!(t.conj(&c).canonical_under(SymSet::all()).0, canonical);
+        }
+
+        // Only the symmetries in the set are used
+        let mut drud = SymSet::default();
+        (0..16).for_each(|i| drud.insert(Sym48::from_coord(i)));
+        let (canonical, _) = c.canonical_under(drud);
+        for t in (0..16).map(Sym::from_coord) {
+            assert_eq!(t.conj(&c).canonical_under(drud).0, canonical);
+        }
+        assert_eq!(
+            c.canonical_under(SymSet::default()),
+            (c.clone(), Sym48::from_coord(0))
+        );
+
+        let set = Sym48::all()
+            .map(|t| UpToSym::new(&t.conj(&c)))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(set.len(), 1);
+        assert!(!set.contains(&UpToSym::new(&c.inverse())));
+
+        // Cubes are only equal up to the symmetries in the set
+        let set = Sym48::all()
+            .map(|t| UpToSym::with_syms(&t.conj(&c), drud))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&UpToSym::with_syms(&Sym::UR.conj(&c), drud)));
+    }
 }
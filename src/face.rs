@@ -1,5 +1,13 @@
 use std::fmt::Display;
 
+/// Axis through the centres of two opposite faces.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Axis {
+    UD,
+    FB,
+    RL,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Face {
     U,
@@ -11,6 +19,24 @@ pub enum Face {
 }
 
 impl Face {
+    pub const fn coord(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn all() -> &'static [Face] {
+        use Face::*;
+        &[U, D, F, B, R, L]
+    }
+
+    pub fn axis(&self) -> Axis {
+        use Face::*;
+        match self {
+            U | D => Axis::UD,
+            F | B => Axis::FB,
+            R | L => Axis::RL,
+        }
+    }
+
     pub fn opposite(&self) -> Self {
         use Face::*;
         match self {
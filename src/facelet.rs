@@ -0,0 +1,460 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::*;
+
+/// Slots of the 54 facelets of a facelet string, in URFDLB face order. Each face is read
+/// row by row, as it appears in the net.
+const FACELET_ORDER: &str = r#"
+    UBL.0 UB.0 UBR.0 UL.0 u UR.0 UFL.0 UF.0 UFR.0
+    UFR.2 UR.1 UBR.1 FR.1 r BR.1 DFR.1 DR.1 DBR.2
+    UFL.2 UF.1 UFR.1 FL.0 f FR.0 DFL.1 DF.1 DFR.2
+    DFL.0 DF.0 DFR.0 DL.0 d DR.0 DBL.0 DB.0 DBR.0
+    UBL.2 UL.1 UFL.1 BL.1 l FL.1 DBL.1 DL.1 DFL.2
+    UBR.2 UB.1 UBL.1 BR.0 b BL.0 DBR.1 DB.1 DBL.2
+"#;
+
+/// Slots of the stickers of the 9-line net, with the U face on top and the L, F, R, B faces
+/// in the middle rows.
+const NET_LAYOUT: &str = r#"
+                      UBL.0 UB.0 UBR.0
+                      UL.0   u    UR.0
+                      UFL.0 UF.0 UFR.0
+    UBL.2 UL.1 UFL.1  UFL.2 UF.1 UFR.1  UFR.2 UR.1 UBR.1  UBR.2 UB.1 UBL.1
+    BL.1   l    FL.1  FL.0   f    FR.0  FR.1   r    BR.1  BR.0   b    BL.0
+    DBL.1 DL.1 DFL.2  DFL.1 DF.1 DFR.2  DFR.1 DR.1 DBR.2  DBR.1 DB.1 DBL.2
+                      DFL.0 DF.0 DFR.0
+                      DL.0   d    DR.0
+                      DBL.0 DB.0 DBR.0
+"#;
+
+/// The colour of each face, used to render a cube.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ColorScheme([char; 6]);
+
+impl ColorScheme {
+    /// Every face is coloured with its own letter.
+    pub const FACES: Self = Self(['U', 'D', 'F', 'B', 'R', 'L']);
+
+    /// Colours given in `Face` order: U, D, F, B, R, L.
+    pub const fn new(colors: [char; 6]) -> Self {
+        Self(colors)
+    }
+
+    pub fn color(&self, f: Face) -> char {
+        self.0[f.coord()]
+    }
+}
+
+/// Reasons a cube description can fail to parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CubeParseError {
+    /// The net has the given number of lines instead of 9.
+    LineCount(usize),
+    /// The given (1-indexed) line of the net has the wrong number of tokens.
+    TokenCount(usize, usize),
+    /// The facelet string has the given number of facelets instead of 54.
+    FaceletCount(usize),
+    /// The sticker was given more than once.
+    DuplicateSticker(Sticker),
+    /// The sticker was never given.
+    MissingSticker(Sticker),
+    /// Two centres have the same colour.
+    DuplicateCenter(char),
+    /// The sticker's colour does not match any centre.
+    UnknownColor(Sticker, char),
+    /// The stickers at the slot do not form a real edge.
+    ImpossibleEdge(Edge, [char; 2]),
+    /// The stickers at the slot do not form a real corner.
+    ImpossibleCorner(Corner, [char; 3]),
+    /// The edge appears in more than one slot.
+    DuplicateEdge(Edge),
+    /// The corner appears in more than one slot.
+    DuplicateCorner(Corner),
+}
+
+impl Display for CubeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use CubeParseError::*;
+        match self {
+            LineCount(n) => write!(f, "expected 9 lines, got {n}"),
+            TokenCount(line, n) => write!(f, "line {line} has {n} tokens"),
+            FaceletCount(n) => write!(f, "expected 54 facelets, got {n}"),
+            DuplicateSticker(s) => write!(f, "sticker {s} is given more than once"),
+            MissingSticker(s) => write!(f, "sticker {s} is missing"),
+            DuplicateCenter(c) => write!(f, "two centres have colour {c}"),
+            UnknownColor(s, c) => write!(f, "sticker {s} has colour {c} which is not a centre"),
+            ImpossibleEdge(e, [a, b]) => {
+                write!(f, "stickers at {e} form impossible edge {a}/{b}")
+            }
+            ImpossibleCorner(k, [a, b, c]) => {
+                write!(f, "stickers at {k} form impossible corner {a}/{b}/{c}")
+            }
+            DuplicateEdge(e) => write!(f, "two pieces map to edge {e}"),
+            DuplicateCorner(k) => write!(f, "two pieces map to corner {k}"),
+        }
+    }
+}
+
+impl std::error::Error for CubeParseError {}
+
+/// A single sticker position on the cube.
+/// - `Center(f)` is the centre of face `f`
+/// - `Edge(e, i)` is sticker `i` of the edge slot `e`, following `Edge::faces`
+/// - `Corner(k, i)` is sticker `i` of the corner slot `k`, following `Corner::faces`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sticker {
+    Center(Face),
+    Edge(Edge, usize),
+    Corner(Corner, usize),
+}
+
+impl Display for Sticker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sticker::Center(face) => write!(f, "{}", face.to_string().to_lowercase()),
+            Sticker::Edge(e, i) => write!(f, "{}.{}", e, i),
+            Sticker::Corner(k, i) => write!(f, "{}.{}", k, i),
+        }
+    }
+}
+
+impl TryFrom<&str> for Sticker {
+    type Error = ();
+
+    /// Parse a sticker written as in the layout tables, e.g. `u`, `UF.1` or `DBL.2`.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if let Ok(f) = Face::try_from(s) {
+            return Ok(Sticker::Center(f));
+        }
+        let (p, i) = s.split_once('.').ok_or(())?;
+        let i = i.parse::<usize>().map_err(|_| ())?;
+        if let Ok(e) = Edge::try_from(p)
+            && i < 2
+        {
+            Ok(Sticker::Edge(e, i))
+        } else if let Ok(k) = Corner::try_from(p)
+            && i < 3
+        {
+            Ok(Sticker::Corner(k, i))
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl Sticker {
+    /// All 54 stickers in facelet string order.
+    pub fn facelet_order() -> Vec<Sticker> {
+        FACELET_ORDER
+            .split_whitespace()
+            .map(|s| Sticker::try_from(s).unwrap())
+            .collect()
+    }
+
+    /// Stickers of each row of the net.
+    pub fn net_layout() -> Vec<Vec<Sticker>> {
+        NET_LAYOUT
+            .lines()
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|s| Sticker::try_from(s).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+impl Cube {
+    /// Build a cube from the colours of its stickers. The colours of the centres determine which
+    /// face each colour belongs to, and every sticker of the cube must be given exactly once.
+    pub fn from_stickers(stickers: &[(Sticker, char)]) -> Result<Self, CubeParseError> {
+        let mut centers = [None; 6];
+        let mut edges = [[None; 2]; 12];
+        let mut corners = [[None; 3]; 8];
+        for (s, c) in stickers {
+            let slot = match *s {
+                Sticker::Center(f) => &mut centers[f.coord()],
+                Sticker::Edge(e, i) => &mut edges[e.coord()][i],
+                Sticker::Corner(k, i) => &mut corners[k.coord()][i],
+            };
+            if slot.replace(*c).is_some() {
+                return Err(CubeParseError::DuplicateSticker(*s));
+            }
+        }
+
+        let mut faces: HashMap<char, Face> = HashMap::new();
+        for f in Face::all() {
+            let c =
+                centers[f.coord()].ok_or(CubeParseError::MissingSticker(Sticker::Center(*f)))?;
+            if faces.insert(c, *f).is_some() {
+                return Err(CubeParseError::DuplicateCenter(c));
+            }
+        }
+        let face = |s: Sticker, c: Option<char>| -> Result<Face, CubeParseError> {
+            let c = c.ok_or(CubeParseError::MissingSticker(s))?;
+            faces
+                .get(&c)
+                .copied()
+                .ok_or(CubeParseError::UnknownColor(s, c))
+        };
+
+        let mut ep = [None; 12];
+        let mut eo = vec![];
+        for dest in Edge::all() {
+            let colors = edges[dest.coord()];
+            let src = [
+                face(Sticker::Edge(*dest, 0), colors[0])?,
+                face(Sticker::Edge(*dest, 1), colors[1])?,
+            ];
+            let m = src.iter().min().cloned().unwrap();
+            let flip = src.iter().position(|f| *f == m).unwrap();
+            if flip != 0 {
+                eo.push(*dest);
+            }
+
+            let src: Edge = (src[0], src[1])
+                .try_into()
+                .map_err(|_| CubeParseError::ImpossibleEdge(*dest, colors.map(Option::unwrap)))?;
+            if ep[src.coord()].replace(dest.coord()).is_some() {
+                return Err(CubeParseError::DuplicateEdge(src));
+            }
+        }
+        let ep = Perm::<12>::from_dests(&ep.map(Option::unwrap));
+        let eo = EO::from_bad_edges(&eo);
+
+        let mut cp = [None; 8];
+        let mut co = vec![];
+        for dest in Corner::all() {
+            let colors = corners[dest.coord()];
+            let src = [
+                face(Sticker::Corner(*dest, 0), colors[0])?,
+                face(Sticker::Corner(*dest, 1), colors[1])?,
+                face(Sticker::Corner(*dest, 2), colors[2])?,
+            ];
+            let m = src.iter().min().cloned().unwrap();
+            let twist = src.iter().position(|f| *f == m).unwrap();
+            co.push((*dest, twist as u8));
+
+            // Mirrored stickers name a real corner too, so the stickers must also follow the
+            // corner's own sticker order
+            let impossible = || CubeParseError::ImpossibleCorner(*dest, colors.map(Option::unwrap));
+            let corner: Corner = (src[0], src[1], src[2])
+                .try_into()
+                .map_err(|_| impossible())?;
+            if (0..3).any(|i| src[i] != corner.faces()[(i + 3 - twist) % 3]) {
+                return Err(impossible());
+            }
+            let src = corner;
+            if cp[src.coord()].replace(dest.coord()).is_some() {
+                return Err(CubeParseError::DuplicateCorner(src));
+            }
+        }
+        let cp = Perm::<8>::from_dests(&cp.map(Option::unwrap));
+        let co = CO::from_assoc(&co);
+
+        Ok(Cube::new(eo, co, ep, cp))
+    }
+
+    /// The face whose colour is shown by the given sticker.
+    pub fn sticker_face(&self, s: Sticker) -> Face {
+        match s {
+            Sticker::Center(f) => f,
+            Sticker::Edge(slot, i) => {
+                let flip = self.eo.is_bad(slot) as usize;
+                self.edge_at(slot).faces()[(i + flip) % 2]
+            }
+            Sticker::Corner(slot, i) => {
+                let twist = self.co.twist(slot) as usize;
+                self.corner_at(slot).faces()[(i + 3 - twist) % 3]
+            }
+        }
+    }
+
+    /// Parse a 54-character facelet string, listing the faces `U`, `R`, `F`, `D`, `L`, `B` in
+    /// that order.
+    pub fn from_facelets(s: &str) -> Result<Self, CubeParseError> {
+        let chars = s.trim().chars().collect::<Vec<_>>();
+        if chars.len() != 54 {
+            return Err(CubeParseError::FaceletCount(chars.len()));
+        }
+        let stickers = Sticker::facelet_order()
+            .into_iter()
+            .zip(chars)
+            .collect::<Vec<_>>();
+        Self::from_stickers(&stickers)
+    }
+
+    /// Produce the 54-character facelet string of the cube, the inverse of `from_facelets`.
+    pub fn to_facelets(&self) -> String {
+        Sticker::facelet_order()
+            .into_iter()
+            .map(|s| self.sticker_face(s).to_string())
+            .collect()
+    }
+
+    /// Parse the 9-line net, where each sticker is given by the first character of a
+    /// whitespace-separated token.
+    pub fn from_net(s: &str) -> Result<Self, CubeParseError> {
+        let layout = Sticker::net_layout();
+        let lines = s.lines().take(layout.len()).collect::<Vec<_>>();
+        if lines.len() != layout.len() {
+            return Err(CubeParseError::LineCount(lines.len()));
+        }
+        let mut stickers = vec![];
+        for (i, line) in lines.into_iter().enumerate() {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if tokens.len() != layout[i].len() {
+                return Err(CubeParseError::TokenCount(i + 1, tokens.len()));
+            }
+            for (s, c) in layout[i].iter().zip(tokens) {
+                stickers.push((*s, c.chars().next().unwrap()));
+            }
+        }
+        Self::from_stickers(&stickers)
+    }
+
+    /// Render the cube as the 9-line net accepted by `from_net`.
+    pub fn to_net(&self, scheme: &ColorScheme) -> String {
+        let mut ret = String::new();
+        for line in Sticker::net_layout() {
+            if line.len() == 3 {
+                ret += "      ";
+            }
+            let colors = line
+                .iter()
+                .map(|s| scheme.color(self.sticker_face(*s)).to_string())
+                .collect::<Vec<_>>();
+            ret += &colors.join(" ");
+            ret += "\n";
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_solved_facelets() {
+        let s = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+        assert_eq!(Cube::default().to_facelets(), s);
+        assert!(Cube::from_facelets(s).unwrap().is_solved());
+    }
+
+    #[test]
+    fn test_facelets_round_trip() {
+        let alg = Alg::try_from("R U R' U' F' L2 D B' R2 U2 F D' L").unwrap();
+        let mut c = Cube::default();
+        for m in alg.moves() {
+            c = c.apply_move(*m);
+            assert_eq!(Cube::from_facelets(&c.to_facelets()), Ok(c.clone()));
+        }
+    }
+
+    #[test]
+    fn test_net_round_trip() {
+        let scheme = ColorScheme::new(['1', '6', '3', '5', '4', '2']);
+        let alg = Alg::try_from("B2 R2 D L2 F2 L2 U2 B2 D L2 D' F2 U' B' R U L' B' D R").unwrap();
+        let mut c = Cube::default();
+        for m in alg.moves() {
+            c = c.apply_move(*m);
+            assert_eq!(Cube::from_net(&c.to_net(&scheme)), Ok(c.clone()));
+        }
+    }
+
+    #[test]
+    fn test_solved_net() {
+        let scheme = ColorScheme::new(['1', '6', '3', '5', '4', '2']);
+        let net = Cube::default().to_net(&scheme);
+        assert_eq!(net.lines().next(), Some("      1 1 1"));
+        assert_eq!(net.lines().nth(4), Some("2 2 2 3 3 3 4 4 4 5 5 5"));
+    }
+
+    #[test]
+    fn test_net_errors() {
+        let scheme = ColorScheme::new(['1', '6', '3', '5', '4', '2']);
+        let net = Cube::default().to_net(&scheme);
+        let edit = |line: usize, col: usize, c: char| {
+            let mut lines = net.lines().map(|l| l.to_owned()).collect::<Vec<_>>();
+            let mut tokens = lines[line].split_whitespace().collect::<Vec<_>>();
+            let c = c.to_string();
+            tokens[col] = &c;
+            lines[line] = tokens.join(" ");
+            lines.join("\n")
+        };
+
+        let short = net.lines().take(5).collect::<Vec<_>>().join("\n");
+        assert_eq!(Cube::from_net(&short), Err(CubeParseError::LineCount(5)));
+
+        let extra = net.replacen("5 5 5", "5 5 5 5", 1);
+        assert_eq!(
+            Cube::from_net(&extra).unwrap_err().to_string(),
+            "line 4 has 13 tokens"
+        );
+
+        // UF.0 shows the F colour, so two F stickers are on one edge
+        assert_eq!(
+            Cube::from_net(&edit(2, 1, '3')).unwrap_err().to_string(),
+            "stickers at UF form impossible edge 3/3"
+        );
+
+        // u shows the D colour
+        assert_eq!(
+            Cube::from_net(&edit(1, 1, '6')),
+            Err(CubeParseError::DuplicateCenter('6'))
+        );
+
+        // UFR.0 shows the R colour, so two R stickers are on one corner
+        assert_eq!(
+            Cube::from_net(&edit(2, 2, '4')).unwrap_err().to_string(),
+            "stickers at UFR form impossible corner 4/3/4"
+        );
+
+        assert_eq!(
+            Cube::from_net(&edit(0, 0, 'x')),
+            Err(CubeParseError::UnknownColor(
+                Sticker::Corner(Corner::UBL, 0),
+                'x'
+            ))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_edge() {
+        // Swap the UF and DF stickers on the F face only, turning UF into a second DF edge
+        let mut s = Cube::default().to_facelets().chars().collect::<Vec<_>>();
+        s[7] = 'D';
+        s[18 + 1] = 'F';
+        let s = s.into_iter().collect::<String>();
+        assert_eq!(
+            Cube::from_facelets(&s),
+            Err(CubeParseError::DuplicateEdge(Edge::DF))
+        );
+    }
+
+    #[test]
+    fn test_mirrored_corner() {
+        // Swap the R and F stickers of UFR, which no physical corner has
+        let mut s = Cube::default().to_facelets().chars().collect::<Vec<_>>();
+        s.swap(9, 18 + 2);
+        let s = s.into_iter().collect::<String>();
+        assert_eq!(
+            Cube::from_facelets(&s),
+            Err(CubeParseError::ImpossibleCorner(
+                Corner::UFR,
+                ['U', 'R', 'F']
+            ))
+        );
+    }
+
+    #[test]
+    fn test_facelets_move() {
+        // Kociemba's reference facelet strings for single moves
+        let r = Cube::from(Move::R).to_facelets();
+        assert_eq!(r, "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB");
+        let f = Cube::from(Move::F).to_facelets();
+        assert_eq!(f, "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB");
+    }
+}
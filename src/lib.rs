@@ -0,0 +1,34 @@
+mod alg;
+mod coord;
+mod cube;
+mod face;
+mod facelet;
+mod math;
+mod metric;
+mod mov;
+mod notation;
+mod optimal;
+mod perm;
+mod piece;
+mod pruning;
+mod solver;
+mod sym;
+mod sym48;
+mod table_file;
+
+pub use alg::*;
+pub use coord::*;
+pub use cube::*;
+pub use face::*;
+pub use facelet::*;
+use math::*;
+pub use metric::*;
+pub use mov::*;
+pub use notation::*;
+pub use optimal::*;
+pub use perm::*;
+pub use piece::*;
+pub use pruning::*;
+pub use solver::*;
+pub use sym::*;
+pub use sym48::*;
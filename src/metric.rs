@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use crate::*;
+
+/// A way of counting the length of a move sequence.
+/// - HTM: every face turn counts as 1
+/// - QTM: quarter turns count as 1, half turns as 2
+/// - STM: as HTM, but a slice move (opposite faces turned the same amount in opposite
+///   directions, like `R L'` or `U2 D2`) counts as 1
+/// - ATM: as HTM, but any two turns of opposite faces made at once count as 1
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Metric {
+    #[default]
+    Htm,
+    Qtm,
+    Stm,
+    Atm,
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Metric::*;
+        match self {
+            Htm => write!(f, "HTM"),
+            Qtm => write!(f, "QTM"),
+            Stm => write!(f, "STM"),
+            Atm => write!(f, "ATM"),
+        }
+    }
+}
+
+impl Metric {
+    pub fn all() -> &'static [Metric] {
+        use Metric::*;
+        &[Htm, Qtm, Stm, Atm]
+    }
+
+    /// Cost of the move `m` when it directly follows `prev`. A move which combines with `prev`
+    /// into a single move of the metric is free.
+    pub fn cost(&self, prev: Option<Move>, m: Move) -> i32 {
+        use Metric::*;
+        let paired = |p: Move| p.face() == m.face().opposite();
+        match self {
+            Htm => 1,
+            Qtm if m.is_half_turn() => 2,
+            Qtm => 1,
+            Stm if prev.is_some_and(|p| paired(p) && (p.decompose().1 + m.decompose().1) == 4) => 0,
+            Atm if prev.is_some_and(paired) => 0,
+            Stm | Atm => 1,
+        }
+    }
+
+    /// Whether some move following `prev` in canonical order can be free.
+    pub fn allows_free_move(&self, prev: Option<Move>) -> bool {
+        matches!(self, Metric::Stm | Metric::Atm)
+            && prev.is_some_and(|p| p.face() < p.face().opposite())
+    }
+
+    /// Length of the sequence of moves in this metric.
+    pub fn len(&self, moves: &[Move]) -> i32 {
+        let mut prev = None;
+        let mut ret = 0;
+        for m in moves {
+            let cost = self.cost(prev, *m);
+            ret += cost;
+            // A move can be combined with at most one other move
+            prev = if cost == 0 { None } else { Some(*m) };
+        }
+        ret
+    }
+
+    /// Upper bound on the length in this metric of a sequence with the given HTM length.
+    pub fn scale_depth(&self, htm_len: i32) -> i32 {
+        match self {
+            Metric::Qtm => 2 * htm_len,
+            _ => htm_len,
+        }
+    }
+
+    /// The cubes reachable with a single move of the metric using face turns from the moveset,
+    /// along with their costs. This includes combined moves, like `U D'` for STM.
+    pub fn generators(&self, moveset: &[Move]) -> Vec<(Cube, i32)> {
+        let mut ret = vec![];
+        for a in moveset {
+            ret.push((Cube::from(*a), self.cost(None, *a)));
+            for b in moveset {
+                if a.face() < b.face() && self.cost(Some(*a), *b) == 0 {
+                    let c = Cube::from(*a).apply_move(*b);
+                    ret.push((c, self.cost(None, *a)));
+                }
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_len() {
+        let a = Alg::try_from("R L' U2 D2 F B R2 U").unwrap();
+        assert_eq!(Metric::Htm.len(a.moves()), 8);
+        assert_eq!(Metric::Qtm.len(a.moves()), 11);
+        assert_eq!(Metric::Stm.len(a.moves()), 6);
+        assert_eq!(Metric::Atm.len(a.moves()), 5);
+
+        // A move can only be combined once
+        let a = Alg::try_from("R L R'").unwrap();
+        assert_eq!(Metric::Atm.len(a.moves()), 2);
+    }
+
+    #[test]
+    fn test_generators() {
+        assert_eq!(Metric::Htm.generators(Move::all()).len(), 18);
+        assert_eq!(Metric::Qtm.generators(Move::all()).len(), 18);
+        assert_eq!(Metric::Stm.generators(Move::all()).len(), 18 + 9);
+        assert_eq!(Metric::Atm.generators(Move::all()).len(), 18 + 27);
+        assert_eq!(
+            Metric::Atm.generators(Move::drud_moveset()).len(),
+            10 + 9 + 2
+        );
+    }
+}
@@ -91,6 +91,23 @@ impl Move {
         &[U, U2, U3, D, D2, D3, F2, B2, R2, L2]
     }
 
+    /// The move turning the given face clockwise `n` times, or `None` if `n` is a multiple of 4.
+    pub fn new(face: Face, n: i32) -> Option<Self> {
+        use Move::*;
+        let moves = match face {
+            Face::U => [U, U2, U3],
+            Face::D => [D, D2, D3],
+            Face::F => [F, F2, F3],
+            Face::B => [B, B2, B3],
+            Face::R => [R, R2, R3],
+            Face::L => [L, L2, L3],
+        };
+        match n.rem_euclid(4) {
+            0 => None,
+            n => Some(moves[n as usize - 1]),
+        }
+    }
+
     /// The face that this move turns.
     pub fn face(&self) -> Face {
         use Move::*;
@@ -0,0 +1,138 @@
+use crate::*;
+
+/// Whole-cube rotation by `n` turns in the direction of a clockwise turn of the face `f`, so that
+/// `R` is an `x` rotation, `U` is a `y` rotation and `F` is a `z` rotation. This moves the corners
+/// like turning `f` and the opposite face the other way, which picks out the rotation.
+fn rotation(f: Face, n: i32) -> Sym48 {
+    let turns = Alg::from(
+        Move::new(f, n)
+            .into_iter()
+            .chain(Move::new(f.opposite(), -n))
+            .collect::<Vec<_>>(),
+    )
+    .to_cube();
+    Sym48::all()
+        .find(|s| !s.is_mirror() && s.conjugator().cp == turns.cp)
+        .unwrap()
+}
+
+/// The turn of the face `f` as seen in the orientation reached by the rotation `o`.
+fn turn(o: Sym48, f: Face, n: i32) -> Option<Move> {
+    let c = o.conj(&Cube::from(Move::new(f, 1)?));
+    let face = Move::all().iter().find(|m| Cube::from(**m) == c)?.face();
+    Move::new(face, n)
+}
+
+/// A single step of an extended move: a turn of a face in the current orientation, or a
+/// whole-cube rotation.
+enum Step {
+    Turn(Face, i32),
+    Rotate(Face, i32),
+}
+
+/// Rewrite a single token of extended notation into face turns and rotations.
+fn parse_token(token: &str) -> Result<Vec<Step>, String> {
+    use Face::*;
+    use Step::*;
+    let err = || format!("Invalid move: {token}");
+    let (base, n) = if let Some(base) = token.strip_suffix("2'") {
+        (base, 2)
+    } else if let Some(base) = token.strip_suffix('2') {
+        (base, 2)
+    } else if let Some(base) = token.strip_suffix('\'') {
+        (base, 3)
+    } else {
+        (token, 1)
+    };
+    let steps = match base {
+        "M" => vec![Turn(R, n), Turn(L, -n), Rotate(L, n)],
+        "E" => vec![Turn(U, n), Turn(D, -n), Rotate(D, n)],
+        "S" => vec![Turn(F, -n), Turn(B, n), Rotate(F, n)],
+        "x" => vec![Rotate(R, n)],
+        "y" => vec![Rotate(U, n)],
+        "z" => vec![Rotate(F, n)],
+        _ => {
+            let mut chars = base.chars();
+            let c = chars.next().ok_or_else(err)?;
+            let wide = match chars.as_str() {
+                "" => c.is_ascii_lowercase(),
+                "w" if c.is_ascii_uppercase() => true,
+                _ => return Err(err()),
+            };
+            let f = Face::try_from(c).map_err(|_| err())?;
+            if wide {
+                vec![Turn(f.opposite(), n), Rotate(f, n)]
+            } else {
+                vec![Turn(f, n)]
+            }
+        }
+    };
+    Ok(steps)
+}
+
+/// Parse a sequence of moves which may contain wide moves (`Rw`, `r`), slice moves (`M`, `E`,
+/// `S`) and cube rotations (`x`, `y`, `z`). These are rewritten into outer face turns by
+/// tracking the orientation of the cube, so the resulting moves have the same effect on the
+/// pieces relative to the centres.
+pub fn parse_moves(s: &str) -> Result<Vec<Move>, String> {
+    let mut o = Sym48::from_coord(0);
+    let mut ret = vec![];
+    for token in s.split_whitespace() {
+        for step in parse_token(token)? {
+            match step {
+                Step::Turn(f, n) => ret.extend(turn(o, f, n)),
+                Step::Rotate(f, n) => o = o.compose(rotation(f, n)),
+            }
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn parse(s: &str) -> Vec<Move> {
+        parse_moves(s).unwrap()
+    }
+
+    #[test]
+    fn test_rotations() {
+        use Move::*;
+        assert_eq!(parse("x U x'"), vec![F]);
+        assert_eq!(parse("y F y'"), vec![R]);
+        assert_eq!(parse("z U z'"), vec![L]);
+        assert_eq!(parse("x2 y z' R"), parse("x2 y z' x' x R"));
+        assert_eq!(parse("x2 U z2 U"), vec![D, U]);
+        assert_eq!(parse("y y y y U"), vec![U]);
+    }
+
+    #[test]
+    fn test_wide_and_slice() {
+        use Move::*;
+        assert_eq!(parse("r U"), vec![L, F]);
+        assert_eq!(parse("Rw U"), parse("r U"));
+        assert_eq!(parse("M U"), vec![R, L3, B]);
+        assert_eq!(parse("E2 F"), vec![U2, D2, B]);
+        assert_eq!(parse("S' U"), vec![F, B3, R]);
+
+        // H perm
+        let h = Alg::from(parse("M2 U M2 U2 M2 U M2")).to_cube();
+        let ep = Perm::<12>::from_swap(Edge::UF.coord(), Edge::UB.coord())
+            .compose(Perm::<12>::from_swap(Edge::UL.coord(), Edge::UR.coord()));
+        assert_eq!(
+            h,
+            Cube {
+                ep,
+                ..Cube::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse_moves("R Q").is_err());
+        assert!(parse_moves("uw").is_err());
+        assert!(parse_moves("Mw").is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::*;
+
+/// Solver which finds provably minimal (HTM) solutions with IDA* over all moves. The heuristic
+/// is the maximum of a corner database (CP × CO) reduced by symmetry and inversion, looked up for
+/// both the cube and its inverse, and the flip × slice × twist table, all of which bound the
+/// distance to the solved cube. The latter stores distances modulo 3, which are made exact during
+/// the search from the distance of the parent.
+///
+/// Building the tables takes much longer than the default two-phase tables, and deep states
+/// may take minutes to solve. The symmetry-reduced coordinates of the two tables are parameters
+/// so that smaller tables can stand in for them, which keeps the bounds valid.
+pub struct OptimalSolver<P = Antisym<CoordCP>, F = CoordFlipSlice>
+where
+    P: SymCoord,
+    F: SymCoord,
+{
+    pt_corners: PrunTable<P, CoordCO>,
+    pt_fst: PrunTable<F, CoordCO, Mod3>,
+}
+
+impl Default for OptimalSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimalSolver {
+    /// Build the pruning tables.
+    pub fn new() -> Self {
+        Self::build()
+    }
+}
+
+impl<P, F> OptimalSolver<P, F>
+where
+    P: SymCoord,
+    F: SymCoord,
+{
+    fn build() -> Self {
+        Self {
+            pt_corners: PrunTable::new(Move::all()),
+            pt_fst: PrunTable::new(Move::all()),
+        }
+    }
+
+    /// Lower bound on the number of moves needed to solve the cube.
+    pub fn eval(&self, c: &Cube) -> i32 {
+        Heuristic::eval(self, c, None).0
+    }
+
+    /// Memory used by the tables in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.pt_corners.memory_usage() + self.pt_fst.memory_usage()
+    }
+
+    /// Find a solution of minimal length.
+    pub fn solve(&self, c: &Cube) -> Result<Alg, SolveError> {
+        c.validate().map_err(SolveError::Unsolvable)?;
+        search(c, self, 20).ok_or(SolveError::NoSolution)
+    }
+
+    /// Find every solution of at most `max_len` moves, shortest first. As in the two-phase
+    /// search, no two solutions differ only in the order of moves on opposite faces.
+    pub fn enumerate(&self, c: &Cube, max_len: i32) -> Result<Vec<Alg>, SolveError> {
+        c.validate().map_err(SolveError::Unsolvable)?;
+        Ok(search_all(c, self, max_len))
+    }
+}
+
+/// Lower bound on the distance to the solved cube, which may be computed from information about
+/// the parent cube.
+trait Heuristic {
+    /// Information passed from a cube to the evaluation of its children.
+    type State: Copy;
+
+    /// The bound for the cube, given the state of its parent if it has one.
+    fn eval(&self, c: &Cube, parent: Option<Self::State>) -> (i32, Self::State);
+}
+
+impl<F> Heuristic for F
+where
+    F: Fn(&Cube) -> i32,
+{
+    type State = ();
+
+    fn eval(&self, c: &Cube, _: Option<()>) -> (i32, ()) {
+        (self(c), ())
+    }
+}
+
+impl<P, F> Heuristic for OptimalSolver<P, F>
+where
+    P: SymCoord,
+    F: SymCoord,
+{
+    /// Exact distance in the flip × slice × twist table.
+    type State = i32;
+
+    fn eval(&self, c: &Cube, parent: Option<i32>) -> (i32, i32) {
+        let fst = match parent {
+            Some(d) => self.pt_fst.eval_child(c, d),
+            None => self.pt_fst.eval_exact(c, Move::all(), Metric::Htm),
+        };
+        (fst.max(self.pt_corners.eval_with_inverse(c)), fst)
+    }
+}
+
+/// Iterative deepening search for a shortest solution of length at most `max_len`, where `h`
+/// must never overestimate the distance to the solved cube.
+fn search<H>(c: &Cube, h: &H, max_len: i32) -> Option<Alg>
+where
+    H: Heuristic,
+{
+    let mut s = Ida {
+        stack: Vec::new(),
+        h,
+        found: None,
+    };
+    for len in h.eval(c, None).0..=max_len {
+        if s.search(c.clone(), None, len) {
+            return Some(Alg::new(s.stack));
+        }
+    }
+    None
+}
+
+/// Find every solution of length at most `max_len`, shortest first.
+fn search_all<H>(c: &Cube, h: &H, max_len: i32) -> Vec<Alg>
+where
+    H: Heuristic,
+{
+    let mut s = Ida {
+        stack: Vec::new(),
+        h,
+        found: Some(Vec::new()),
+    };
+    for len in h.eval(c, None).0..=max_len {
+        s.search(c.clone(), None, len);
+    }
+    s.found.unwrap_or_default()
+}
+
+struct Ida<'a, H>
+where
+    H: Heuristic,
+{
+    stack: Vec<Move>,
+    h: &'a H,
+    /// Solutions found so far when enumerating, otherwise the search stops at the first one.
+    found: Option<Vec<Alg>>,
+}
+
+impl<H> Ida<'_, H>
+where
+    H: Heuristic,
+{
+    /// Search for a solution of exactly `len` moves, leaving it on the stack if one is found.
+    /// When enumerating, every solution is collected instead and this always returns false.
+    fn search(&mut self, c: Cube, parent: Option<H::State>, len: i32) -> bool {
+        if self.stack.len() as i32 == len {
+            if let Some(found) = &mut self.found
+                && c.is_solved()
+            {
+                found.push(Alg::new(self.stack.clone()));
+                return false;
+            }
+            return c.is_solved();
+        }
+        let (bound, state) = self.h.eval(&c, parent);
+        if self.stack.len() as i32 + bound > len {
+            return false;
+        }
+        for m in Move::all() {
+            if let Some(last) = self.stack.last()
+                && (last.cancels_with(m) || last.commutes_with(m) && m < last)
+            {
+                continue;
+            }
+            self.stack.push(*m);
+            if self.search(c.apply_move(*m), Some(state), len) {
+                return true;
+            }
+            self.stack.pop();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        // The phase 1 tables alone are a valid, if weak, heuristic for short scrambles
+        let pt_co = PrunTable::<CoordCO, CoordESlice>::new(Move::all());
+        let pt_eo = PrunTable::<CoordEO, CoordESlice>::new(Move::all());
+        let eval = |c: &Cube| pt_co.eval(c).max(pt_eo.eval(c));
+
+        let c = Alg::try_from("R U2 F' L D B2").unwrap().to_cube();
+        let sol = search(&c, &eval, 20).unwrap();
+        assert!(sol.apply(&c).is_solved());
+        assert_eq!(sol.len(), 6);
+
+        // Redundant moves are not part of the optimal solution
+        let c = Alg::try_from("R U R' F F' R U' R'").unwrap().to_cube();
+        assert_eq!(search(&c, &eval, 20), Some(Alg::default()));
+
+        let c = Alg::try_from("R U R' U' R U R' U'").unwrap().to_cube();
+        assert_eq!(search(&c, &eval, 20).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_search_all() {
+        let pt_co = PrunTable::<CoordCO, CoordESlice>::new(Move::all());
+        let pt_eo = PrunTable::<CoordEO, CoordESlice>::new(Move::all());
+        let eval = |c: &Cube| pt_co.eval(c).max(pt_eo.eval(c));
+
+        // Commuting moves are only found in one order
+        let c = Alg::try_from("R L").unwrap().to_cube();
+        let sols = search_all(&c, &eval, 3);
+        assert_eq!(sols, vec![Alg::try_from("R' L'").unwrap()]);
+
+        let c = Alg::try_from("R U").unwrap().to_cube();
+        let sols = search_all(&c, &eval, 4);
+        assert_eq!(sols[0], Alg::try_from("U' R'").unwrap());
+        for sol in &sols {
+            assert!(sol.apply(&c).is_solved());
+            assert_eq!(&sol.simplify(), sol);
+        }
+    }
+
+    #[test]
+    fn test_optimal_small_tables() {
+        // EO × CO tables in place of the real ones are quick to build, and searching with the
+        // solver's heuristic must still find every shortest solution that brute force finds
+        let solver = OptimalSolver::<CoordEO, CoordEO>::build();
+        let brute_force = |_: &Cube| 0;
+        for scramble in [
+            "R U F",
+            "L2 B' D R",
+            "F R' U2 B L'",
+            "D B2 L' U R2",
+            "U R U' F2 D'",
+        ] {
+            let c = Alg::try_from(scramble).unwrap().to_cube();
+            let len = search(&c, &brute_force, 5).unwrap().len();
+            let sol = solver.solve(&c).unwrap();
+            assert!(sol.apply(&c).is_solved());
+            assert_eq!(sol.len(), len);
+            assert_eq!(
+                search_all(&c, &solver, len as i32),
+                search_all(&c, &brute_force, len as i32)
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "building the tables takes several minutes"]
+    fn test_optimal() {
+        let solver = OptimalSolver::new();
+        let c = Alg::try_from("F2 U' R2 B L' D2 F R' U2 B'")
+            .unwrap()
+            .to_cube();
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+        assert!(sol.len() <= 10);
+    }
+}
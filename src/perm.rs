@@ -6,7 +6,7 @@ use crate::*;
 /// A permutation on N <= 16 elements.
 /// The representation is a 64-bit integer, bits [4k, 4k+4) encode where the k-th element
 /// is mapped to by the permutation.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Perm<const N: usize>(u64);
 
 impl<const N: usize> Default for Perm<N> {
@@ -49,7 +49,7 @@ impl<const N: usize> Perm<N> {
         let mut used = [false; N];
         for i in 0..N {
             let j = self.dest(i);
-            if used[j] || j >= N {
+            if j >= N || used[j] {
                 return false;
             }
             used[j] = true;
@@ -113,7 +113,7 @@ impl<const N: usize> Perm<N> {
         for i in 0..N {
             let q = fact(N - 1 - i);
             let ord = index / q;
-            index = index % q;
+            index %= q;
             let (x, _) = used
                 .iter()
                 .enumerate()
@@ -143,7 +143,7 @@ impl<const N: usize> Perm<N> {
 
     fn set_dest_nomask(&mut self, i: usize, dest: usize) {
         debug_assert!(i < N && dest < N);
-        self.0 = self.0 | ((dest as u64) << (i * 4));
+        self.0 |= (dest as u64) << (i * 4);
     }
 
     /// The index that the the item at index `i` comes from
@@ -163,9 +163,9 @@ impl<const N: usize> Perm<N> {
         T: Clone,
     {
         let mut b = [const { None }; N];
-        for i in 0..N {
+        for (i, x) in a.iter().enumerate() {
             let j = self.dest(i);
-            b[j] = Some(a[i].clone());
+            b[j] = Some(x.clone());
         }
         b.map(Option::unwrap)
     }
@@ -211,7 +211,7 @@ impl<const N: usize> Perm<N> {
         let mut used = [false; N];
         let mut ans = 0;
         for (i, j) in indices.iter().enumerate() {
-            let x = relabel[self.dest(*j) as usize];
+            let x = relabel[self.dest(*j)];
             let ord = used[0..x as usize].iter().filter(|y| !**y).count();
             ans += ord * perm(N - 1 - i, k - 1 - i);
             used[x as usize] = true;
@@ -268,6 +268,41 @@ impl<const N: usize> Perm<N> {
         ans
     }
 
+    /// A permutation with the given index under `index_partial_unordered`, the given indices
+    /// and the remaining ones each keeping their relative order.
+    pub fn from_index_partial_unordered(indices: u16, mut index: usize) -> Self {
+        // Image of the given indices
+        let k = indices.count_ones() as usize;
+        let mut img = 0u16;
+        for i in (1..=k).rev() {
+            let mut j = i - 1;
+            while binom(j + 1, i) <= index {
+                j += 1;
+            }
+            index -= binom(j, i);
+            img |= 1 << j;
+        }
+        let z = (1 << k) - 1;
+        if img == indices {
+            img = z
+        } else if img == z {
+            img = indices;
+        }
+
+        let mut dests = [0; N];
+        let (mut inside, mut outside) = (img, !img);
+        for (i, dest) in dests.iter_mut().enumerate() {
+            let free = if indices & (1 << i) != 0 {
+                &mut inside
+            } else {
+                &mut outside
+            };
+            *dest = free.trailing_zeros() as usize;
+            *free &= *free - 1;
+        }
+        Self::from_dests(&dests)
+    }
+
     /// Mask the permutation, deleting other indices which are not in the mask. Assumes that
     /// the mask and its complement are disjoint, i.e. no elements are permuted between the two.
     pub fn mask<const K: usize>(&self, indices: &[usize; K]) -> Perm<K> {
@@ -298,6 +333,11 @@ impl<const N: usize> Perm<N> {
         ret
     }
 
+    /// Parity of the permutation, 0 if it is even and 1 if it is odd.
+    pub fn parity(&self) -> usize {
+        self.cycles().iter().map(|c| c.len() - 1).sum::<usize>() % 2
+    }
+
     // Disjoint cycles of the permutation which have length at least 2.
     pub fn cycles(&self) -> Vec<Vec<usize>> {
         let mut vis = [false; N];
@@ -361,4 +401,20 @@ mod test {
             assert_eq!(c, 720 / 20);
         }
     }
+
+    #[test]
+    fn test_from_index_partial_unordered() {
+        assert_eq!(
+            Perm::<12>::from_index_partial_unordered(0xf00, 0),
+            Perm::<12>::new()
+        );
+        for i in 0..495 {
+            let p = Perm::<12>::from_index_partial_unordered(0xf00, i);
+            assert_eq!(p.index_partial_unordered(0xf00), i);
+        }
+        for i in 0..20 {
+            let p = Perm::<6>::from_index_partial_unordered(0b010101, i);
+            assert_eq!(p.index_partial_unordered(0b010101), i);
+        }
+    }
 }
@@ -2,7 +2,7 @@ use crate::*;
 
 use std::fmt::{self, Display, Formatter};
 
-#[derive(Debug, Copy, Clone, Eq, Ord, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Edge {
     UF,
     UL,
@@ -25,8 +25,9 @@ impl TryFrom<&str> for Edge {
         if s.len() != 2 {
             return Err(());
         }
-        let c1: Face = s.chars().nth(0).unwrap().try_into().unwrap();
-        let c2: Face = s.chars().nth(1).unwrap().try_into().unwrap();
+        let mut chars = s.chars();
+        let c1: Face = chars.next().unwrap().try_into()?;
+        let c2: Face = chars.next().unwrap().try_into()?;
         (c1, c2).try_into()
     }
 }
@@ -105,21 +106,30 @@ impl Edge {
         use Edge::*;
         &[UF, UL, UB, UR, DF, DL, DB, DR, FR, FL, BL, BR]
     }
-}
-
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        self.coord() == other.coord()
-    }
-}
 
-impl PartialOrd for Edge {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.coord().partial_cmp(&other.coord())
+    /// The faces of the edge's stickers, in sticker order. Sticker 0 is the one on the U/D face,
+    /// or the F/B face for E-slice edges.
+    pub fn faces(&self) -> [Face; 2] {
+        use Edge::*;
+        use Face::*;
+        match self {
+            UF => [U, F],
+            UL => [U, L],
+            UB => [U, B],
+            UR => [U, R],
+            DF => [D, F],
+            DL => [D, L],
+            DB => [D, B],
+            DR => [D, R],
+            FR => [F, R],
+            FL => [F, L],
+            BL => [B, L],
+            BR => [B, R],
+        }
     }
 }
 
-#[derive(Copy, Debug, Clone, Eq, Ord, Hash)]
+#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Corner {
     UFR,
     UFL,
@@ -140,9 +150,9 @@ impl TryFrom<&str> for Corner {
         }
 
         let mut chars = s.chars();
-        let f1: Face = chars.next().unwrap().try_into().unwrap();
-        let f2: Face = chars.next().unwrap().try_into().unwrap();
-        let f3: Face = chars.next().unwrap().try_into().unwrap();
+        let f1: Face = chars.next().unwrap().try_into()?;
+        let f2: Face = chars.next().unwrap().try_into()?;
+        let f3: Face = chars.next().unwrap().try_into()?;
         (f1, f2, f3).try_into()
     }
 }
@@ -191,20 +201,40 @@ impl Corner {
         }
     }
 
-    pub fn all() -> &'static [Corner] {
+    pub fn from_coord(coord: usize) -> Self {
         use Corner::*;
-        &[UFR, UFL, UBL, UBR, DFR, DFL, DBL, DBR]
+        match coord {
+            0 => UFR,
+            1 => UFL,
+            2 => UBL,
+            3 => UBR,
+            4 => DFR,
+            5 => DFL,
+            6 => DBL,
+            7 => DBR,
+            _ => panic!("Invalid corner coordinate: {}", coord),
+        }
     }
-}
 
-impl PartialEq for Corner {
-    fn eq(&self, other: &Self) -> bool {
-        self.coord() == other.coord()
+    pub fn all() -> &'static [Corner] {
+        use Corner::*;
+        &[UFR, UFL, UBL, UBR, DFR, DFL, DBL, DBR]
     }
-}
 
-impl PartialOrd for Corner {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.coord().partial_cmp(&other.coord())
+    /// The faces of the corner's stickers, in sticker order. Sticker 0 is the one on the U/D
+    /// face, and the remaining stickers follow counter-clockwise around the corner.
+    pub fn faces(&self) -> [Face; 3] {
+        use Corner::*;
+        use Face::*;
+        match self {
+            UFR => [U, F, R],
+            UFL => [U, L, F],
+            UBL => [U, B, L],
+            UBR => [U, R, B],
+            DFR => [D, R, F],
+            DFL => [D, F, L],
+            DBL => [D, L, B],
+            DBR => [D, B, R],
+        }
     }
 }
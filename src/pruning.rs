@@ -1,59 +1,535 @@
-use std::marker::PhantomData;
+use std::{
+    borrow::Cow,
+    io,
+    marker::PhantomData,
+    ops::Range,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
 
 use crate::*;
 use coord::*;
+use table_file::*;
 
-/// Pruning table for a composite coordinate (R, C), where R is reduced by symmetry.
-pub struct PrunTable<R, C>
+/// Tables generated by the build script with the `embed-tables` feature.
+#[cfg(embedded_tables)]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+}
+
+/// Storage for the distances of a pruning table, where every entry starts out unknown.
+pub trait Storage: Sized {
+    /// Name of the storage format, recorded in saved tables.
+    const NAME: &'static str;
+
+    /// Largest distance which can be stored, larger distances are stored as this instead.
+    const MAX_DIST: u8;
+
+    /// Whether entries hold exact distances, rather than distances modulo 3.
+    const EXACT: bool = true;
+
+    /// Storage the table is built in, which must hold exact distances.
+    type Builder: Storage + Sync;
+
+    fn new(len: usize) -> Self;
+
+    fn get(&self, i: usize) -> Option<u8>;
+
+    fn set(&mut self, i: usize, dist: u8);
+
+    /// The raw entries, as saved to a file.
+    fn as_bytes(&self) -> &[u8];
+
+    /// Storage for `len` entries from the raw entries, if there are the right number of them.
+    /// Borrowed entries are used in place until the storage is modified.
+    fn from_bytes(bytes: Cow<'static, [u8]>, len: usize) -> Option<Self>;
+
+    /// Storage for `len` entries holding the distances of a built table.
+    fn from_builder(b: Self::Builder, len: usize) -> Self;
+}
+
+/// Storage using a byte for each entry.
+pub struct Bytes(Cow<'static, [u8]>);
+
+impl Storage for Bytes {
+    const NAME: &'static str = "Bytes";
+    const MAX_DIST: u8 = 0xfe;
+    type Builder = Self;
+
+    fn new(len: usize) -> Self {
+        Self(Cow::Owned(vec![0xff; len]))
+    }
+
+    fn get(&self, i: usize) -> Option<u8> {
+        match self.0[i] {
+            0xff => None,
+            d => Some(d),
+        }
+    }
+
+    fn set(&mut self, i: usize, dist: u8) {
+        self.0.to_mut()[i] = dist;
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn from_bytes(bytes: Cow<'static, [u8]>, len: usize) -> Option<Self> {
+        (bytes.len() == len).then_some(Self(bytes))
+    }
+
+    fn from_builder(b: Self, _: usize) -> Self {
+        b
+    }
+}
+
+/// Storage using 4 bits for each entry, for large tables with small distances.
+pub struct Nibbles(Cow<'static, [u8]>);
+
+impl Storage for Nibbles {
+    const NAME: &'static str = "Nibbles";
+    const MAX_DIST: u8 = 0xe;
+    type Builder = Self;
+
+    fn new(len: usize) -> Self {
+        Self(Cow::Owned(vec![0xff; len.div_ceil(2)]))
+    }
+
+    fn get(&self, i: usize) -> Option<u8> {
+        match (self.0[i / 2] >> (4 * (i % 2))) & 0xf {
+            0xf => None,
+            d => Some(d),
+        }
+    }
+
+    fn set(&mut self, i: usize, dist: u8) {
+        let shift = 4 * (i % 2);
+        let b = &mut self.0.to_mut()[i / 2];
+        *b = (*b & !(0xf << shift)) | (dist << shift);
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn from_bytes(bytes: Cow<'static, [u8]>, len: usize) -> Option<Self> {
+        (bytes.len() == len.div_ceil(2)).then_some(Self(bytes))
+    }
+
+    fn from_builder(b: Self, _: usize) -> Self {
+        b
+    }
+}
+
+/// Storage using 2 bits for each entry, holding the distance modulo 3. The exact distance of a
+/// cube can be recovered from that of a neighbour, see `PrunTable::eval_child`, as long as every
+/// move costs 1 in the metric. The table is built with a byte for each entry.
+pub struct Mod3(Cow<'static, [u8]>);
+
+impl Storage for Mod3 {
+    const NAME: &'static str = "Mod3";
+    const MAX_DIST: u8 = Bytes::MAX_DIST;
+    const EXACT: bool = false;
+    type Builder = Bytes;
+
+    fn new(len: usize) -> Self {
+        Self(Cow::Owned(vec![0xff; len.div_ceil(4)]))
+    }
+
+    fn get(&self, i: usize) -> Option<u8> {
+        match (self.0[i / 4] >> (2 * (i % 4))) & 0x3 {
+            0x3 => None,
+            d => Some(d),
+        }
+    }
+
+    fn set(&mut self, i: usize, dist: u8) {
+        let shift = 2 * (i % 4);
+        let b = &mut self.0.to_mut()[i / 4];
+        *b = (*b & !(0x3 << shift)) | ((dist % 3) << shift);
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn from_bytes(bytes: Cow<'static, [u8]>, len: usize) -> Option<Self> {
+        (bytes.len() == len.div_ceil(4)).then_some(Self(bytes))
+    }
+
+    fn from_builder(b: Bytes, len: usize) -> Self {
+        let mut ret = Self::new(len);
+        for i in 0..len {
+            ret.set(i, b.get(i).unwrap_or(Self::MAX_DIST));
+        }
+        ret
+    }
+}
+
+/// Set of entries of a table, shared by the threads to collect the entries they find while
+/// building.
+struct BitSet(Vec<AtomicU64>);
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self((0..len.div_ceil(64)).map(|_| AtomicU64::new(0)).collect())
+    }
+
+    fn insert(&self, i: usize) {
+        self.0[i / 64].fetch_or(1 << (i % 64), Ordering::Relaxed);
+    }
+
+    /// Iterate over the entries, removing them from the set.
+    fn drain(&mut self) -> impl Iterator<Item = usize> {
+        self.0.iter_mut().enumerate().flat_map(|(k, w)| {
+            let mut w = std::mem::take(w.get_mut());
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    None
+                } else {
+                    let pos = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    Some(64 * k + pos)
+                }
+            })
+        })
+    }
+}
+
+/// Pruning table for a composite coordinate (R, C), where R is reduced by symmetry, storing
+/// distances in `S`.
+pub struct PrunTable<R, C, S = Bytes>
 where
-    R: Coord,
+    R: SymCoord,
     C: Coord,
+    S: Storage,
 {
     rsym: SymTable<R>,
-    dist: Vec<u8>,
+    dist: S,
     _r: PhantomData<R>,
     _c: PhantomData<C>,
 }
 
-impl<R, C> PrunTable<R, C>
+impl<R, C, S> PrunTable<R, C, S>
 where
-    R: Coord,
+    R: SymCoord,
     C: Coord,
+    S: Storage,
 {
+    /// Build the table for HTM distances under the moveset.
     pub fn new(moveset: &'static [Move]) -> Self {
-        let rsym = SymTable::new();
-        let mut dist = vec![None; rsym.n_conj_classes() * C::N_VALUES];
-        let mut q: VecDeque<(Cube, usize)> = VecDeque::new();
-        dist[0] = Some(0);
-        q.push_back((Cube::default(), 0));
-        while let Some((a, d)) = q.pop_front() {
-            for m in moveset {
-                let b = rsym.canonicalize(&a.apply_move(*m));
-                let b_coord = Self::coord_no_canonicalize(&rsym, &b);
-                if dist[b_coord].is_none() {
-                    // This is a new family of coordinates we haven't seen before
-                    for s in rsym.self_syms(&b) {
-                        let c = C::conj(&b, s);
-                        let c_coord = Self::coord_no_canonicalize(&rsym, &c);
-                        dist[c_coord] = Some((d + 1) as u8);
+        Self::with_metric(moveset, Metric::Htm)
+    }
+
+    /// Build the table for distances in the given metric under the moveset. With the
+    /// `embed-tables` feature, the default solver's tables are instead views over data compiled
+    /// into the library.
+    pub fn with_metric(moveset: &'static [Move], metric: Metric) -> Self {
+        #[cfg(embedded_tables)]
+        if let Some(ret) = Self::embedded(moveset, metric) {
+            return ret;
+        }
+        let gens = metric.generators(moveset);
+        assert!(
+            S::EXACT || gens.iter().all(|(_, cost)| *cost == 1),
+            "distances modulo 3 need every move to cost 1"
+        );
+        assert!(
+            !R::ANTISYM || Self::inverse_compatible(moveset),
+            "{} cannot be reduced by inversion",
+            Self::name_of()
+        );
+        let rsym = SymTable::with_syms(&Self::syms());
+        let n = rsym.n_conj_classes() * C::N_VALUES;
+        let mut dist = S::Builder::new(n);
+        dist.set(0, 0);
+        // Expand the entries level by level, scanning the table in parallel. Moves may cost more
+        // than 1 in some metrics, so an entry can be reached again with a shorter distance before
+        // its level is expanded. Once most of the table is reached, it is quicker to look for
+        // neighbours at the current level from the entries which haven't been reached. The threads
+        // record what they find in one shared set for each move cost.
+        let max_cost = gens.iter().map(|(_, cost)| *cost).max().unwrap_or(1) as usize;
+        let threads = thread::available_parallelism().map_or(1, |t| t.get());
+        let chunk = n.div_ceil(threads);
+        let mut found = (0..max_cost).map(|_| BitSet::new(n)).collect::<Vec<_>>();
+        let mut reached = 1;
+        let mut max_dist = 0;
+        let mut d = 0;
+        while d <= max_dist && d < S::Builder::MAX_DIST {
+            let backward = reached > n / 2;
+            thread::scope(|s| {
+                for start in (0..n).step_by(chunk) {
+                    let (rsym, dist, gens, found) = (&rsym, &dist, &gens, &found);
+                    let range = start..(start + chunk).min(n);
+                    s.spawn(move || {
+                        if backward {
+                            Self::expand_backward(rsym, dist, gens, range, d, found);
+                        } else {
+                            Self::expand_forward(rsym, dist, gens, range, d, found);
+                        }
+                    });
+                }
+            });
+            for (cost, entries) in found.iter_mut().enumerate() {
+                let db = (d as usize + cost + 1).min(S::Builder::MAX_DIST as usize) as u8;
+                for i in entries.drain() {
+                    match dist.get(i) {
+                        None => reached += 1,
+                        Some(x) if x > db => {}
+                        Some(_) => continue,
                     }
-                    q.push_back((b, d + 1));
+                    dist.set(i, db);
+                    max_dist = max_dist.max(db);
                 }
             }
+            d += 1;
+        }
+        // Anything not reached is further away than the storage can represent
+        for i in 0..n {
+            if dist.get(i).is_none() {
+                dist.set(i, S::Builder::MAX_DIST);
+            }
         }
-        let dist = dist.into_iter().map(Option::unwrap).collect::<Vec<_>>();
         Self {
+            rsym,
+            dist: S::from_builder(dist, n),
+            _r: PhantomData,
+            _c: PhantomData,
+        }
+    }
+
+    /// Symmetries of `R` which `C` is also compatible with, which the table is reduced by.
+    fn syms() -> Vec<Sym> {
+        R::syms()
+            .iter()
+            .copied()
+            .filter(|s| C::SYMS.iter().any(|t| t.coord() == s.coord()))
+            .collect()
+    }
+
+    /// Whether the coordinates of the inverse of a cube are determined by those of the cube, and
+    /// the inverse is reachable with the moveset, as needed for reducing the table by inversion.
+    fn inverse_compatible(moveset: &[Move]) -> bool {
+        let closed = moveset.iter().all(|m| {
+            let (face, n) = m.decompose();
+            moveset.contains(&Move::new(face, -n).unwrap())
+        });
+        (R::INVERTIBLE || R::INVERTIBLE_WITH_CP && C::HAS_CP)
+            && (C::INVERTIBLE || C::INVERTIBLE_WITH_CP && R::HAS_CP)
+            && closed
+    }
+
+    /// Cubes one move away from the cube, along with the cost of the move. When the table is
+    /// reduced by inversion, the cube stands for its inverse too, so moves are also applied to the
+    /// inverse, which is the same as applying them before the cube.
+    fn neighbours<'a>(
+        a: &'a Cube,
+        gens: &'a [(Cube, i32)],
+    ) -> impl Iterator<Item = (Cube, i32)> + 'a {
+        let inverse = R::ANTISYM.then(|| a.inverse());
+        let bases = std::iter::once(a.clone()).chain(inverse);
+        bases.flat_map(move |a| gens.iter().map(move |(g, cost)| (a.compose(g), *cost)))
+    }
+
+    /// Find the entries one move away from the entries in the range at distance `d`, which have
+    /// not been reached or have only been reached further away. Entries are recorded in `found`
+    /// by the cost of the move reaching them.
+    fn expand_forward(
+        rsym: &SymTable<R>,
+        dist: &S::Builder,
+        gens: &[(Cube, i32)],
+        range: Range<usize>,
+        d: u8,
+        found: &[BitSet],
+    ) {
+        for i in range {
+            if dist.get(i) != Some(d) {
+                continue;
+            }
+            let a = Self::entry_cube(rsym, i);
+            for (b, cost) in Self::neighbours(&a, gens) {
+                let b = rsym.canonicalize(&b);
+                let b_coord = Self::coord_no_canonicalize(rsym, &b);
+                let db = d as i32 + cost;
+                if dist.get(b_coord).is_none_or(|x| x as i32 > db) {
+                    // This is a family of coordinates we haven't seen before, or have only
+                    // seen further away
+                    for (s, inv) in rsym.self_syms(&b) {
+                        let c = if inv { b.inverse() } else { b.clone() };
+                        let c = C::conj(&c, s);
+                        let c_coord = Self::coord_no_canonicalize(rsym, &c);
+                        found[cost as usize - 1].insert(c_coord);
+                    }
+                }
+            }
+        }
+    }
+
+    /// As `expand_forward`, but finding the entries by checking whether any neighbour of each
+    /// entry in the range is at distance `d`.
+    fn expand_backward(
+        rsym: &SymTable<R>,
+        dist: &S::Builder,
+        gens: &[(Cube, i32)],
+        range: Range<usize>,
+        d: u8,
+        found: &[BitSet],
+    ) {
+        for i in range {
+            if dist.get(i).is_some_and(|x| x <= d + 1) {
+                continue;
+            }
+            let a = Self::entry_cube(rsym, i);
+            // Every move has the same cost as its inverse, so this is also the cheapest move
+            // from a neighbour at distance `d`
+            let cost = Self::neighbours(&a, gens)
+                .filter(|(b, _)| {
+                    let b = rsym.canonicalize(b);
+                    dist.get(Self::coord_no_canonicalize(rsym, &b)) == Some(d)
+                })
+                .map(|(_, cost)| cost)
+                .min();
+            if let Some(cost) = cost
+                && dist.get(i).is_none_or(|x| x as i32 > d as i32 + cost)
+            {
+                found[cost as usize - 1].insert(i);
+            }
+        }
+    }
+
+    /// Write the table to a file, see `load`.
+    pub fn save(&self, path: impl AsRef<Path>, moveset: &[Move], metric: Metric) -> io::Result<()> {
+        let rsym = self.rsym.to_bytes();
+        let mut payload = (rsym.len() as u32).to_le_bytes().to_vec();
+        payload.extend(rsym);
+        payload.extend(self.dist.as_bytes());
+        write_table(path.as_ref(), &Self::header(moveset, metric), &payload)
+    }
+
+    /// Read a table written by `save` for the same moveset and metric, failing if the file was
+    /// written for a different table, by another version of the format, or is corrupt.
+    pub fn load(path: impl AsRef<Path>, moveset: &[Move], metric: Metric) -> io::Result<Self> {
+        let payload = read_table(path.as_ref(), &Self::header(moveset, metric))?;
+        Self::from_payload(Cow::Owned(payload))
+    }
+
+    /// The table compiled into the library by the build script, if there is one.
+    #[cfg(embedded_tables)]
+    fn embedded(moveset: &[Move], metric: Metric) -> Option<Self> {
+        let name = Self::file_name(moveset, metric);
+        let (_, bytes) = embedded::TABLES.iter().find(|(n, _)| *n == name)?;
+        let payload = parse_table(bytes, &Self::header(moveset, metric)).ok()?;
+        Self::from_payload(Cow::Borrowed(payload)).ok()
+    }
+
+    fn from_payload(payload: Cow<'static, [u8]>) -> io::Result<Self> {
+        let mut r = Reader::new(&payload);
+        let n = r.u32()? as usize;
+        let rsym = SymTable::from_bytes(r.take(n)?)?;
+        let dist = match payload {
+            Cow::Borrowed(p) => Cow::Borrowed(&p[4 + n..]),
+            Cow::Owned(mut p) => {
+                p.drain(..4 + n);
+                Cow::Owned(p)
+            }
+        };
+        let n = rsym.n_conj_classes() * C::N_VALUES;
+        let dist = S::from_bytes(dist, n).ok_or_else(|| invalid("invalid pruning table"))?;
+        Ok(Self {
             rsym,
             dist,
             _r: PhantomData,
             _c: PhantomData,
+        })
+    }
+
+    /// Load the table from the file, or build it and try to save it there if it can't be loaded.
+    pub fn load_or_generate(
+        path: impl AsRef<Path>,
+        moveset: &'static [Move],
+        metric: Metric,
+    ) -> Self {
+        Self::load(&path, moveset, metric).unwrap_or_else(|_| {
+            let ret = Self::with_metric(moveset, metric);
+            let _ = ret.save(&path, moveset, metric);
+            ret
+        })
+    }
+
+    /// File name distinguishing the table from those for other coordinates, movesets and
+    /// metrics.
+    pub fn file_name(moveset: &[Move], metric: Metric) -> String {
+        let h = Self::header(moveset, metric).fingerprint;
+        format!("{}_{}_{}_{:016x}.bin", R::NAME, C::NAME, metric, h)
+    }
+
+    fn header(moveset: &[Move], metric: Metric) -> TableHeader {
+        TableHeader {
+            names: vec![R::NAME, C::NAME, S::NAME],
+            n_values: vec![R::N_VALUES, C::N_VALUES],
+            fingerprint: moveset_fingerprint(moveset, metric, &Self::syms(), R::ANTISYM),
         }
     }
 
     /// Given a cube, produce a lower bound on the number of moves to reduce the coordinate to 0.
+    /// For tables storing distances modulo 3 this is only the remainder, see `eval_child`.
     pub fn eval(&self, c: &Cube) -> i32 {
         let coord = Self::coord(&self.rsym, c);
-        self.dist[coord] as i32
+        self.dist.get(coord).unwrap_or(S::MAX_DIST) as i32
+    }
+
+    /// The larger of the bounds for the cube and its inverse, which are the same distance away.
+    pub fn eval_with_inverse(&self, c: &Cube) -> i32 {
+        self.eval(c).max(self.eval(&c.inverse()))
+    }
+
+    /// Like `eval`, for a cube one move away from a cube at distance `parent`. This is the exact
+    /// distance for tables storing distances modulo 3, when `parent` is exact.
+    pub fn eval_child(&self, c: &Cube, parent: i32) -> i32 {
+        let d = self.eval(c);
+        if S::EXACT {
+            d
+        } else {
+            parent - 1 + (d - parent + 1).rem_euclid(3)
+        }
+    }
+
+    /// Like `eval`, but exact for tables storing distances modulo 3, by following moves which
+    /// bring the cube closer until the coordinate is 0. The moveset and metric must be the ones
+    /// the table was built with.
+    pub fn eval_exact(&self, c: &Cube, moveset: &[Move], metric: Metric) -> i32 {
+        if S::EXACT {
+            return self.eval(c);
+        }
+        let gens = metric.generators(moveset);
+        let mut c = c.clone();
+        let mut d = 0;
+        while Self::coord(&self.rsym, &c) != 0 {
+            let closer = (self.eval(&c) + 2) % 3;
+            c = gens
+                .iter()
+                .map(|(g, _)| c.compose(g))
+                .find(|n| self.eval(n) == closer)
+                .unwrap();
+            d += 1;
+        }
+        d
+    }
+
+    /// Name of the table, from the names of its coordinates.
+    pub fn name(&self) -> String {
+        Self::name_of()
+    }
+
+    fn name_of() -> String {
+        format!("{}+{}", R::NAME, C::NAME)
+    }
+
+    /// Memory used by the table in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.dist.as_bytes().len() + self.rsym.memory_usage()
     }
 
     /// Compute the symmetry-reduced composite coordinate.
@@ -67,13 +543,189 @@ where
     /// Compute the symmetry-reduced composite coordinate for a cube which already has its
     /// symmetry-reduced coordinate canonicalized.
     fn coord_no_canonicalize(rsym: &SymTable<R>, c: &Cube) -> usize {
-        let r = rsym.conj_class(R::index(&c));
-        let c = C::index(&c);
+        let r = rsym.conj_class(R::index(c));
+        let c = C::index(c);
         r * C::N_VALUES + c
     }
 
+    /// A cube with the given symmetry-reduced composite coordinate, which is already
+    /// canonicalized.
+    fn entry_cube(rsym: &SymTable<R>, coord: usize) -> Cube {
+        let (r, c) = Self::decompose_coord(coord);
+        C::set(&R::rep(rsym.class_rep(r)), c)
+    }
+
     /// Decompose the coordinate into its symmetry-composed and basic component, respectively.
     fn decompose_coord(coord: usize) -> (usize, usize) {
         (coord / C::N_VALUES, coord % C::N_VALUES)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_nibbles() {
+        let bytes = PrunTable::<CoordEO, CoordESlice>::new(Move::all());
+        let nibbles = PrunTable::<CoordEO, CoordESlice, Nibbles>::new(Move::all());
+        let mut c = Cube::default();
+        for m in Alg::try_from("R U F' L2 D B' R' F2 U' L").unwrap().moves() {
+            c = c.apply_move(*m);
+            assert_eq!(bytes.eval(&c), nibbles.eval(&c));
+        }
+    }
+
+    #[test]
+    #[cfg(embedded_tables)]
+    fn test_embedded() {
+        use std::borrow::Cow;
+        let table = PrunTable::<CoordCO, CoordESlice>::new(Move::all());
+        assert!(matches!(table.dist.0, Cow::Borrowed(_)));
+        let c = Alg::try_from("R U F").unwrap().to_cube();
+        assert_eq!(table.eval(&c), 3);
+    }
+
+    /// Distances of every (CO, ESlice) pair found by a plain search over all of them.
+    fn reference_distances(metric: Metric) -> Vec<i32> {
+        let index = |c: &Cube| CoordCO::index(c) * CoordESlice::N_VALUES + CoordESlice::index(c);
+        let gens = metric.generators(Move::all());
+        let mut dist = vec![i32::MAX; CoordCO::N_VALUES * CoordESlice::N_VALUES];
+        let mut levels = vec![vec![Cube::default()]];
+        dist[0] = 0;
+        let mut d = 0;
+        while d < levels.len() {
+            for a in std::mem::take(&mut levels[d]) {
+                if dist[index(&a)] != d as i32 {
+                    continue;
+                }
+                for (g, cost) in &gens {
+                    let b = a.compose(g);
+                    let db = d as i32 + cost;
+                    if db < dist[index(&b)] {
+                        dist[index(&b)] = db;
+                        levels.resize(levels.len().max(db as usize + 1), vec![]);
+                        levels[db as usize].push(b);
+                    }
+                }
+            }
+            d += 1;
+        }
+        dist
+    }
+
+    #[test]
+    fn test_generate() {
+        // Checking every entry covers the levels found both forwards and backwards
+        for metric in [Metric::Htm, Metric::Qtm] {
+            let table = PrunTable::<CoordCO, CoordESlice>::with_metric(Move::all(), metric);
+            let dist = reference_distances(metric);
+            for co in 0..CoordCO::N_VALUES {
+                let c = CoordCO::set(&Cube::default(), co);
+                for es in 0..CoordESlice::N_VALUES {
+                    let c = CoordESlice::set(&c, es);
+                    let i = co * CoordESlice::N_VALUES + es;
+                    assert_eq!(table.eval(&c), dist[i], "{metric} {co} {es}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_syms() {
+        let syms = |s: Vec<Sym>| s.iter().map(|s| s.coord()).collect::<Vec<_>>();
+        let coords = |s: &[Sym]| s.iter().map(|s| s.coord()).collect::<Vec<_>>();
+        assert_eq!(
+            syms(PrunTable::<CoordCO, CoordESlice>::syms()),
+            coords(CoordCO::syms())
+        );
+        assert_eq!(
+            syms(PrunTable::<CoordFlipSlice, CoordCO, Mod3>::syms()),
+            coords(CoordFlipSlice::syms())
+        );
+
+        // EO is only preserved by the symmetries which keep the FB axis
+        assert_eq!(
+            syms(PrunTable::<CoordCP, CoordEO>::syms()),
+            coords(CoordEO::syms())
+        );
+    }
+
+    #[test]
+    fn test_antisym() {
+        assert!(PrunTable::<Antisym<CoordCP>, CoordCO>::inverse_compatible(
+            Move::all()
+        ));
+        assert!(
+            PrunTable::<Antisym<CoordCP>, CoordESliceEP>::inverse_compatible(Move::drud_moveset())
+        );
+        // EO of the inverse depends on the permutation of the edges, and U' is not in the moveset
+        assert!(!PrunTable::<Antisym<CoordCP>, CoordEO>::inverse_compatible(
+            Move::all()
+        ));
+        assert!(!PrunTable::<Antisym<CoordCP>, CoordCO>::inverse_compatible(
+            &[Move::U]
+        ));
+
+        let sym = PrunTable::<CoordCP, CoordESliceEP>::new(Move::drud_moveset());
+        let antisym = PrunTable::<Antisym<CoordCP>, CoordESliceEP>::new(Move::drud_moveset());
+        assert!(antisym.dist.as_bytes().len() < sym.dist.as_bytes().len());
+
+        let mut c = Cube::default();
+        for m in Alg::try_from("U R2 D' F2 L2 U2 B2 D R2 U' B2")
+            .unwrap()
+            .moves()
+        {
+            c = c.apply_move(*m);
+            assert_eq!(antisym.eval(&c), sym.eval(&c));
+            assert_eq!(antisym.eval(&c.inverse()), sym.eval(&c.inverse()));
+            assert_eq!(antisym.eval_with_inverse(&c), sym.eval(&c));
+        }
+    }
+
+    #[test]
+    fn test_mod3() {
+        let bytes = PrunTable::<CoordEO, CoordESlice>::new(Move::all());
+        let mod3 = PrunTable::<CoordEO, CoordESlice, Mod3>::new(Move::all());
+        assert!(mod3.memory_usage() < bytes.memory_usage());
+        assert_eq!(mod3.dist.as_bytes().len() * 4, bytes.dist.as_bytes().len());
+
+        let mut c = Cube::default();
+        let mut d = 0;
+        for m in Alg::try_from("R U F' L2 D B' R' F2 U' L B")
+            .unwrap()
+            .moves()
+        {
+            c = c.apply_move(*m);
+            d = mod3.eval_child(&c, d);
+            assert_eq!(d, bytes.eval(&c));
+            assert_eq!(mod3.eval_exact(&c, Move::all(), Metric::Htm), d);
+            assert!(mod3.eval(&c) <= d);
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        type Table = PrunTable<CoordEO, CoordESlice, Nibbles>;
+        let path = std::env::temp_dir().join(format!("prun_eo_{}.bin", std::process::id()));
+        let table = Table::new(Move::all());
+        table.save(&path, Move::all(), Metric::Htm).unwrap();
+        let loaded = Table::load(&path, Move::all(), Metric::Htm).unwrap();
+        assert_eq!(loaded.dist.as_bytes(), table.dist.as_bytes());
+
+        // Tables for another metric or storage are stale
+        assert!(Table::load(&path, Move::all(), Metric::Qtm).is_err());
+        assert!(PrunTable::<CoordEO, CoordESlice>::load(&path, Move::all(), Metric::Htm).is_err());
+
+        // A corrupt table is regenerated and saved again
+        let mut bytes = std::fs::read(&path).unwrap();
+        let n = bytes.len();
+        bytes[n / 2] ^= 0x10;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(Table::load(&path, Move::all(), Metric::Htm).is_err());
+        let regenerated = Table::load_or_generate(&path, Move::all(), Metric::Htm);
+        assert_eq!(regenerated.dist.as_bytes(), table.dist.as_bytes());
+        assert!(Table::load(&path, Move::all(), Metric::Htm).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,896 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::*;
+
+/// Reasons a solve can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    /// The search finished without finding any solution.
+    NoSolution,
+    /// Strict limits were reached before any solution was found.
+    LimitReached,
+    /// The search was cancelled before any solution was found.
+    Cancelled,
+    /// The cube cannot be solved.
+    Unsolvable(Unsolvable),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::NoSolution => write!(f, "no solution found"),
+            SolveError::LimitReached => write!(f, "no solution within limits"),
+            SolveError::Cancelled => write!(f, "search cancelled"),
+            SolveError::Unsolvable(u) => write!(f, "unsolvable cube: {u}"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Reported whenever the search finds a solution shorter than all previous ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolutionFound {
+    pub alg: Alg,
+    /// Length of the solution in the solver's metric.
+    pub len: i32,
+    /// Time since the solve started.
+    pub elapsed: Duration,
+    /// Length of the phase 1 part of the solution in the solver's metric.
+    pub phase1_len: i32,
+}
+
+/// Handle to stop a solve from another thread. Clones share the same flag, and the solve stops
+/// shortly after it is set, returning the best solution so far.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on how long the search goes on for.
+///
+/// By default the limits only stop the search for shorter solutions, and the search goes on
+/// until some solution is found. With `strict` set they stop the search outright, and the solve
+/// fails with `SolveError::LimitReached` if nothing was found by then.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SolveLimits {
+    /// Wall time allowed for each solve.
+    pub time_limit: Option<Duration>,
+
+    /// Time kept in reserve before the time limit, to account for the delay in noticing it.
+    pub time_margin: Duration,
+
+    /// Number of nodes visited across all threads. The node count is checked in batches, so the
+    /// search is deterministic when it runs on a single thread.
+    pub node_limit: Option<usize>,
+
+    /// Stop as soon as a solution at most this long is found.
+    pub target_len: Option<i32>,
+
+    /// Maximum length of phase 1, in HTM (doubled for QTM).
+    pub max_dr_len: i32,
+
+    /// Maximum length of phase 2, in HTM (doubled for QTM).
+    pub max_fin_len: i32,
+
+    /// Whether the limits apply before a solution has been found.
+    pub strict: bool,
+}
+
+impl Default for SolveLimits {
+    fn default() -> Self {
+        Self {
+            time_limit: Some(Duration::from_secs(1)),
+            time_margin: Duration::from_millis(50),
+            node_limit: None,
+            target_len: None,
+            max_dr_len: 20,
+            max_fin_len: 12,
+            strict: false,
+        }
+    }
+}
+
+/// Two-phase solver which owns its pruning tables, so that they are only built once and can be
+/// reused across solves.
+pub struct TwoPhaseSolver {
+    /// Limits on each solve.
+    pub limits: SolveLimits,
+
+    /// Number of threads searching in parallel, each taking a share of the first phase 1 moves.
+    pub threads: usize,
+
+    /// Metric in which solution lengths are measured and minimized.
+    metric: Metric,
+
+    tables: Arc<Tables>,
+}
+
+/// Pruning used to bound the length of phase 1.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Phase1Pruning {
+    /// max(CO+ESlice, EO+ESlice), which is quick to build.
+    #[default]
+    Split,
+    /// A single FlipSlice+CO table with about 140M entries (70 MB), which takes minutes to
+    /// build but prunes deep phase 1 searches far more.
+    FlipSliceTwist,
+}
+
+/// Pruning used to bound the length of phase 2.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Phase2Pruning {
+    /// max(CP+ESliceEP, EP+ESliceEP), which is quick to build.
+    #[default]
+    Split,
+    /// The split tables along with a CP+EP table with about 111M entries (56 MB), which takes
+    /// minutes to build but bounds hard DR states much better. Its distances are capped at 14.
+    CornerEdge,
+}
+
+/// Choice of pruning tables for the solver.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TableOptions {
+    pub phase1: Phase1Pruning,
+    pub phase2: Phase2Pruning,
+    /// Directory in which to save the tables, so that later runs can load them instead of
+    /// building them again.
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Load the table from the cache directory if there is one, otherwise build it.
+fn table<R, C, S>(
+    cache_dir: Option<&Path>,
+    moveset: &'static [Move],
+    metric: Metric,
+) -> PrunTable<R, C, S>
+where
+    R: SymCoord,
+    C: Coord,
+    S: Storage,
+{
+    match cache_dir {
+        Some(dir) => {
+            let path = dir.join(PrunTable::<R, C, S>::file_name(moveset, metric));
+            PrunTable::load_or_generate(path, moveset, metric)
+        }
+        None => PrunTable::with_metric(moveset, metric),
+    }
+}
+
+/// Phase 1 pruning tables, see `Phase1Pruning`.
+enum DrudTables {
+    Split(
+        PrunTable<CoordCO, CoordESlice>,
+        PrunTable<CoordEO, CoordESlice>,
+    ),
+    FlipSliceTwist(PrunTable<CoordFlipSlice, CoordCO, Nibbles>),
+}
+
+/// Pruning tables for both phases, shared read-only between search threads.
+struct Tables {
+    drud: DrudTables,
+    pt_cp: PrunTable<CoordCP, CoordESliceEP>,
+    pt_ep: PrunTable<CoordEP, CoordESliceEP>,
+    pt_cpep: Option<PrunTable<CoordCP, CoordEP, Nibbles>>,
+}
+
+impl Tables {
+    fn eval_drud(&self, c: &Cube) -> i32 {
+        match &self.drud {
+            DrudTables::Split(pt_co, pt_eo) => i32::max(pt_co.eval(c), pt_eo.eval(c)),
+            DrudTables::FlipSliceTwist(pt) => pt.eval(c),
+        }
+    }
+
+    fn memory_usage(&self) -> Vec<(String, usize)> {
+        let mut ret = vec![];
+        match &self.drud {
+            DrudTables::Split(pt_co, pt_eo) => {
+                ret.push((pt_co.name(), pt_co.memory_usage()));
+                ret.push((pt_eo.name(), pt_eo.memory_usage()));
+            }
+            DrudTables::FlipSliceTwist(pt) => ret.push((pt.name(), pt.memory_usage())),
+        }
+        ret.push((self.pt_cp.name(), self.pt_cp.memory_usage()));
+        ret.push((self.pt_ep.name(), self.pt_ep.memory_usage()));
+        if let Some(pt) = &self.pt_cpep {
+            ret.push((pt.name(), pt.memory_usage()));
+        }
+        ret
+    }
+
+    fn eval_fin(&self, c: &Cube) -> i32 {
+        let split = i32::max(self.pt_cp.eval(c), self.pt_ep.eval(c));
+        match &self.pt_cpep {
+            Some(pt) => split.max(pt.eval(c)),
+            None => split,
+        }
+    }
+}
+
+impl Default for TwoPhaseSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TwoPhaseSolver {
+    /// Build the pruning tables for both phases, minimizing solutions in HTM.
+    pub fn new() -> Self {
+        Self::with_metric(Metric::Htm)
+    }
+
+    /// Build the pruning tables for both phases, minimizing solutions in the given metric.
+    pub fn with_metric(metric: Metric) -> Self {
+        Self::with_tables(metric, TableOptions::default())
+    }
+
+    /// Build the chosen pruning tables for both phases, minimizing solutions in the given metric.
+    pub fn with_tables(metric: Metric, options: TableOptions) -> Self {
+        let dir = options.cache_dir.as_deref();
+        let drud = match options.phase1 {
+            Phase1Pruning::Split => DrudTables::Split(
+                table(dir, Move::all(), metric),
+                table(dir, Move::all(), metric),
+            ),
+            Phase1Pruning::FlipSliceTwist => {
+                DrudTables::FlipSliceTwist(table(dir, Move::all(), metric))
+            }
+        };
+        let pt_cpep = match options.phase2 {
+            Phase2Pruning::Split => None,
+            Phase2Pruning::CornerEdge => Some(table(dir, Move::drud_moveset(), metric)),
+        };
+        Self {
+            limits: SolveLimits::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            metric,
+            tables: Arc::new(Tables {
+                drud,
+                pt_cp: table(dir, Move::drud_moveset(), metric),
+                pt_ep: table(dir, Move::drud_moveset(), metric),
+                pt_cpep,
+            }),
+        }
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// Lower bound on the number of moves needed to reach DRUD.
+    pub fn eval_drud(&self, c: &Cube) -> i32 {
+        self.tables.eval_drud(c)
+    }
+
+    /// Lower bound on the number of moves needed to solve a cube which is in DRUD.
+    pub fn eval_fin(&self, c: &Cube) -> i32 {
+        self.tables.eval_fin(c)
+    }
+
+    /// Name and memory used in bytes of each pruning table.
+    pub fn memory_usage(&self) -> Vec<(String, usize)> {
+        self.tables.memory_usage()
+    }
+
+    /// Find a solution to the cube, searching for shorter ones until the limits are reached.
+    /// The cube and its inverse are each searched with DR on all three axes, interleaved by
+    /// phase 1 depth.
+    pub fn solve(&self, c: &Cube) -> Result<Alg, SolveError> {
+        self.solve_with(c, &CancelToken::new(), |_| {})
+    }
+
+    /// As `solve`, but stopping early once `cancel` is triggered, and calling `on_solution` on
+    /// the calling thread with each improved solution as it is found.
+    pub fn solve_with<F>(
+        &self,
+        c: &Cube,
+        cancel: &CancelToken,
+        on_solution: F,
+    ) -> Result<Alg, SolveError>
+    where
+        F: FnMut(&SolutionFound),
+    {
+        self.run(c, cancel, None, on_solution)?
+            .ok_or(SolveError::NoSolution)
+    }
+
+    /// Find every solution of at most `max_len` in the solver's metric, shortest first. The
+    /// solutions are in the canonical order of the search, so no two of them differ only in the
+    /// order of moves on opposite faces. The depth limits and target length are ignored, but
+    /// strict limits still end the enumeration early with an error.
+    pub fn enumerate(&self, c: &Cube, max_len: i32) -> Result<Vec<Alg>, SolveError> {
+        let mut ret = vec![];
+        self.run(c, &CancelToken::new(), Some(max_len), |s| {
+            ret.push(s.alg.clone())
+        })?;
+        ret.sort_by(|a, b| {
+            (a.len_in(self.metric), a.moves()).cmp(&(b.len_in(self.metric), b.moves()))
+        });
+        Ok(ret)
+    }
+
+    fn run<F>(
+        &self,
+        c: &Cube,
+        cancel: &CancelToken,
+        max_len: Option<i32>,
+        mut on_solution: F,
+    ) -> Result<Option<Alg>, SolveError>
+    where
+        F: FnMut(&SolutionFound),
+    {
+        c.validate().map_err(SolveError::Unsolvable)?;
+        let start = Instant::now();
+        let shared = Arc::new(Shared::new(cancel.clone()));
+        let (tx, rx) = mpsc::channel();
+        let n_threads = self.threads.max(1);
+        // The variants would find the same solutions again when enumerating
+        let variants = if max_len.is_some() { 1 } else { 6 };
+        let cubes = Variant::all()[..variants]
+            .iter()
+            .map(|v| v.apply(c))
+            .collect::<Vec<_>>();
+        let handles = (0..n_threads)
+            .map(|thread| {
+                let (cubes, shared, events) = (cubes.clone(), shared.clone(), tx.clone());
+                let (t1, t2) = (self.tables.clone(), self.tables.clone());
+                let mut s = Solver {
+                    start,
+                    limits: self.limits,
+                    max_len,
+                    metric: self.metric,
+                    thread,
+                    n_threads,
+                    variant: 0,
+                    shared,
+                    events,
+                    stack_dr: Vec::new(),
+                    stack_fin: Vec::new(),
+                    eval_drud: move |c: &Cube| t1.eval_drud(c),
+                    eval_fin: move |c: &Cube| t2.eval_fin(c),
+                    nodes: 0,
+                };
+                thread::spawn(move || s.solve(&cubes))
+            })
+            .collect::<Vec<_>>();
+        // The channel closes once every thread has finished
+        drop(tx);
+        for event in rx {
+            on_solution(&event);
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        let best = shared.best.lock().unwrap().take();
+        match best {
+            Some(alg) => Ok(Some(alg)),
+            None if cancel.is_cancelled() => Err(SolveError::Cancelled),
+            None if shared.stop.load(Ordering::Relaxed) => Err(SolveError::LimitReached),
+            None => Ok(None),
+        }
+    }
+}
+
+/// One of the six cubes searched for a given cube: the cube or its inverse, conjugated so that
+/// the axis it is reduced to DR on becomes UD.
+#[derive(Copy, Clone, Debug)]
+struct Variant {
+    axis: Axis,
+    inverse: bool,
+}
+
+impl Variant {
+    fn all() -> [Variant; 6] {
+        [Axis::UD, Axis::FB, Axis::RL]
+            .map(|axis| [false, true].map(|inverse| Variant { axis, inverse }))
+            .as_flattened()
+            .try_into()
+            .unwrap()
+    }
+
+    /// The cube searched for this variant.
+    fn apply(&self, c: &Cube) -> Cube {
+        let c = if self.inverse { c.inverse() } else { c.clone() };
+        Sym::conj_axis(self.axis, &c)
+    }
+
+    /// Map a solution of the searched cube back to a solution of the original cube.
+    fn unmap(&self, alg: &Alg) -> Alg {
+        let alg = alg.unconjugate_axis(self.axis);
+        if self.inverse { alg.inverse() } else { alg }
+    }
+}
+
+/// Search state shared between threads. The best length is kept in an atomic so that every
+/// thread can prune against it without locking.
+struct Shared {
+    best: Mutex<Option<Alg>>,
+    best_len: AtomicI32,
+    nodes: AtomicUsize,
+    stop: AtomicBool,
+    cancel: CancelToken,
+}
+
+impl Shared {
+    fn new(cancel: CancelToken) -> Self {
+        Self {
+            best: Mutex::new(None),
+            best_len: AtomicI32::new(i32::MAX),
+            nodes: AtomicUsize::new(0),
+            stop: AtomicBool::new(false),
+            cancel,
+        }
+    }
+
+    fn best_len(&self) -> Option<i32> {
+        match self.best_len.load(Ordering::Relaxed) {
+            i32::MAX => None,
+            len => Some(len),
+        }
+    }
+
+    /// Record and report the solution if it is shorter than the best one so far.
+    fn offer(&self, sol: SolutionFound, events: &Sender<SolutionFound>) {
+        let mut best = self.best.lock().unwrap();
+        if self.best_len().is_some_and(|best| best <= sol.len) {
+            return;
+        }
+        *best = Some(sol.alg.clone());
+        self.best_len.store(sol.len, Ordering::Relaxed);
+        // Sent while holding the lock, so that events arrive in order of improving length
+        let _ = events.send(sol);
+    }
+}
+
+struct Solver<F1, F2>
+where
+    F1: Fn(&Cube) -> i32,
+    F2: Fn(&Cube) -> i32,
+{
+    pub start: Instant,
+    pub limits: SolveLimits,
+    /// Length up to which every solution is reported, when enumerating.
+    pub max_len: Option<i32>,
+    pub metric: Metric,
+
+    /// Index of this thread, which searches the first phase 1 moves (numbered across all
+    /// variants) with this index modulo `n_threads`.
+    pub thread: usize,
+    pub n_threads: usize,
+    /// Index of the variant currently searched.
+    pub variant: usize,
+    pub shared: Arc<Shared>,
+    pub events: Sender<SolutionFound>,
+
+    pub stack_dr: Vec<Move>,
+    pub stack_fin: Vec<Move>,
+    pub eval_drud: F1,
+    pub eval_fin: F2,
+
+    /// Nodes visited by this thread.
+    pub nodes: usize,
+}
+
+impl<F1, F2> Solver<F1, F2>
+where
+    F1: Fn(&Cube) -> i32,
+    F2: Fn(&Cube) -> i32,
+{
+    fn stopped(&mut self) -> bool {
+        const BATCH: usize = 1024;
+        let mut stop = self.shared.stop.load(Ordering::Relaxed);
+        if !stop && self.nodes.is_multiple_of(BATCH) {
+            let nodes = self.shared.nodes.fetch_add(BATCH, Ordering::Relaxed);
+            let limits = &self.limits;
+            let over = limits
+                .time_limit
+                .is_some_and(|t| self.start.elapsed() > t.saturating_sub(limits.time_margin))
+                || limits.node_limit.is_some_and(|n| nodes >= n);
+            stop = over && (limits.strict || self.shared.best_len().is_some())
+                || self.shared.cancel.is_cancelled();
+            if stop {
+                self.shared.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        self.nodes += 1;
+        stop
+    }
+
+    /// Lower bound on the remaining cost given the evaluation of the current cube. If the next
+    /// move can be free, it may reduce the evaluation by 1 at no cost.
+    fn bound(&self, last: Option<&Move>, eval: i32) -> i32 {
+        if self.metric.allows_free_move(last.copied()) {
+            eval - 1
+        } else {
+            eval
+        }
+    }
+
+    fn solve_fin(&mut self, c: Cube, dr_len: i32, fin_cost: i32, fin_len: i32) {
+        if self.stopped() {
+            return;
+        }
+        if fin_cost == fin_len && c.is_solved() {
+            let sol_len = dr_len + fin_len;
+            let alg = self
+                .stack_dr
+                .iter()
+                .chain(self.stack_fin.iter())
+                .copied()
+                .collect::<Alg>();
+            let alg = Variant::all()[self.variant].unmap(&alg);
+            // Enumerating goes on past the target length
+            if self.max_len.is_none() && self.limits.target_len.is_some_and(|t| sol_len <= t) {
+                self.shared.stop.store(true, Ordering::Relaxed);
+            }
+            let sol = SolutionFound {
+                alg,
+                len: sol_len,
+                elapsed: self.start.elapsed(),
+                phase1_len: dr_len,
+            };
+            if self.max_len.is_some() {
+                let _ = self.events.send(sol);
+            } else {
+                self.shared.offer(sol, &self.events);
+            }
+            return;
+        }
+        let last = self.stack_fin.last().or(self.stack_dr.last()).copied();
+        if fin_cost + self.bound(last.as_ref(), (self.eval_fin)(&c)) > fin_len {
+            return;
+        }
+        for m in Move::drud_moveset() {
+            if self
+                .shared
+                .best_len()
+                .is_some_and(|best| best <= dr_len + fin_len)
+            {
+                break;
+            }
+            if let Some(last) = last
+                && (last.cancels_with(m) || last.commutes_with(m) && *m < last)
+            {
+                continue;
+            }
+            let cost = fin_cost + self.metric.cost(last, *m);
+            if cost > fin_len {
+                continue;
+            }
+            self.stack_fin.push(*m);
+            self.solve_fin(c.apply_move(*m), dr_len, cost, fin_len);
+            self.stack_fin.pop();
+        }
+    }
+
+    fn solve_dr(&mut self, c: Cube, dr_cost: i32, dr_len: i32) {
+        if self.stopped() {
+            return;
+        }
+        // Every thread sees the root, but only one needs to search from it. Otherwise phase 1
+        // must end with a move outside the DRUD moveset, since any solution with a longer
+        // phase 1 ending in DRUD moves was already found with those moves in phase 2.
+        let last = self.stack_dr.last().copied();
+        let handoff = match last {
+            Some(m) => !Move::drud_moveset().contains(&m),
+            None => self.variant % self.n_threads == self.thread,
+        };
+        if dr_cost == dr_len && c.is_drud() && handoff {
+            let max_fin = match self.max_len {
+                Some(max_len) => max_len - dr_len,
+                None => self.metric.scale_depth(self.limits.max_fin_len),
+            };
+            // No phase 2 shorter than the bound can succeed
+            let min_fin = self.bound(last.as_ref(), (self.eval_fin)(&c)).max(0);
+            for target_fin in min_fin..=max_fin {
+                self.solve_fin(c.clone(), dr_len, 0, target_fin);
+            }
+        }
+        if dr_cost + self.bound(last.as_ref(), (self.eval_drud)(&c)) > dr_len {
+            return;
+        }
+        for (i, m) in Move::all().iter().enumerate() {
+            let branch = self.variant * Move::all().len() + i;
+            if self.stack_dr.is_empty() && branch % self.n_threads != self.thread {
+                continue;
+            }
+            if let Some(last) = last
+                && (last.cancels_with(m) || last.commutes_with(m) && *m < last)
+            {
+                continue;
+            }
+            let cost = dr_cost + self.metric.cost(last, *m);
+            if cost > dr_len {
+                continue;
+            }
+            self.stack_dr.push(*m);
+            self.solve_dr(c.apply_move(*m), cost, dr_len);
+            self.stack_dr.pop();
+        }
+    }
+
+    fn solve(&mut self, cubes: &[Cube]) {
+        let max_dr = self
+            .max_len
+            .unwrap_or(self.metric.scale_depth(self.limits.max_dr_len));
+        for dr_len in 0..=max_dr {
+            for (variant, c) in cubes.iter().enumerate() {
+                if self.shared.best_len().is_some_and(|best| dr_len >= best) {
+                    return;
+                }
+                self.variant = variant;
+                self.solve_dr(c.clone(), 0, dr_len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_solve() {
+        let solver = TwoPhaseSolver::new();
+        let scramble =
+            Alg::try_from("B2 R2 D L2 F2 L2 U2 B2 D L2 D' F2 U' B' R U L' B' D R D' L2 B' U'")
+                .unwrap();
+        let c = scramble.to_cube();
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+        assert!(sol.len() <= 22);
+
+        let flip = Cube {
+            eo: EO::from_bad_edges(&[Edge::UF]),
+            ..Cube::default()
+        };
+        assert_eq!(
+            solver.solve(&flip),
+            Err(SolveError::Unsolvable(Unsolvable::EdgeFlip))
+        );
+    }
+
+    #[test]
+    fn test_solve_qtm() {
+        let solver = TwoPhaseSolver::with_metric(Metric::Qtm);
+        let c = Alg::try_from("R2 U F' L D2 B R' U2 F L2 D' B2")
+            .unwrap()
+            .to_cube();
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+
+        // Half turns cost 2, so a short HTM solution should avoid them
+        let c = Alg::try_from("R2").unwrap().to_cube();
+        assert_eq!(solver.solve(&c).unwrap().len_in(Metric::Qtm), 2);
+    }
+
+    #[test]
+    fn test_solve_atm() {
+        let solver = TwoPhaseSolver::with_metric(Metric::Atm);
+        let c = Alg::try_from("R L' U D2 F B'").unwrap().to_cube();
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+        assert_eq!(sol.len_in(Metric::Atm), 3);
+    }
+
+    #[test]
+    fn test_solve_threads() {
+        let mut solver = TwoPhaseSolver::new();
+        solver.threads = 4;
+        let c = Alg::try_from("D2 F' R U2 B L' D F2 R' U B2 L")
+            .unwrap()
+            .to_cube();
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+
+        // Cubes already in DRUD are only searched from the root once
+        let c = Alg::try_from("U R2 D' F2").unwrap().to_cube();
+        assert_eq!(solver.solve(&c).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut solver = TwoPhaseSolver::new();
+        solver.threads = 1;
+        let c = Alg::try_from("D2 F' R U2 B L' D F2 R' U B2 L")
+            .unwrap()
+            .to_cube();
+
+        // Node limits are deterministic on a single thread
+        solver.limits = SolveLimits {
+            time_limit: None,
+            node_limit: Some(200_000),
+            ..SolveLimits::default()
+        };
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+        assert_eq!(solver.solve(&c), Ok(sol));
+
+        // The first solution within the target is returned
+        solver.limits = SolveLimits {
+            time_limit: None,
+            target_len: Some(30),
+            ..SolveLimits::default()
+        };
+        assert!(solver.solve(&c).unwrap().len() <= 30);
+
+        solver.limits = SolveLimits {
+            node_limit: Some(0),
+            strict: true,
+            ..SolveLimits::default()
+        };
+        assert_eq!(solver.solve(&c), Err(SolveError::LimitReached));
+
+        // Each phase is too short to solve a single move
+        solver.limits = SolveLimits {
+            max_dr_len: 0,
+            max_fin_len: 0,
+            ..SolveLimits::default()
+        };
+        let c = Alg::try_from("R").unwrap().to_cube();
+        assert_eq!(solver.solve(&c), Err(SolveError::NoSolution));
+    }
+
+    #[test]
+    fn test_events() {
+        let solver = TwoPhaseSolver::new();
+        let c = Alg::try_from("L2 U F' R D2 B' U2 L F2 D' R2 B")
+            .unwrap()
+            .to_cube();
+        let mut events = vec![];
+        let sol = solver
+            .solve_with(&c, &CancelToken::new(), |s| events.push(s.clone()))
+            .unwrap();
+        assert_eq!(events.last().map(|s| &s.alg), Some(&sol));
+        for s in &events {
+            assert!(s.alg.apply(&c).is_solved());
+            assert_eq!(s.alg.len() as i32, s.len);
+            assert!(s.phase1_len <= s.len);
+        }
+        assert!(events.windows(2).all(|w| w[0].len > w[1].len));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut solver = TwoPhaseSolver::new();
+        let c = Alg::try_from("L2 U F' R D2 B' U2 L F2 D' R2 B")
+            .unwrap()
+            .to_cube();
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(
+            solver.solve_with(&c, &cancel, |_| {}),
+            Err(SolveError::Cancelled)
+        );
+
+        // Without a time limit, only the cancellation stops the search
+        solver.limits.time_limit = None;
+        let cancel = CancelToken::new();
+        let start = std::time::Instant::now();
+        let res = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                cancel.cancel();
+            });
+            solver.solve_with(&c, &cancel, |_| {})
+        });
+        assert!(start.elapsed().as_secs() < 10);
+        assert!(res.is_ok_and(|sol| sol.apply(&c).is_solved()));
+    }
+
+    #[test]
+    fn test_enumerate() {
+        fn brute_force(c: &Cube, stack: &mut Vec<Move>, max_len: usize, ret: &mut Vec<Alg>) {
+            if c.is_solved() {
+                ret.push(Alg::new(stack.clone()));
+            }
+            if stack.len() == max_len {
+                return;
+            }
+            for m in Move::all() {
+                if let Some(last) = stack.last()
+                    && (last.cancels_with(m) || last.commutes_with(m) && m < last)
+                {
+                    continue;
+                }
+                stack.push(*m);
+                brute_force(&c.apply_move(*m), stack, max_len, ret);
+                stack.pop();
+            }
+        }
+
+        let mut solver = TwoPhaseSolver::new();
+        solver.threads = 2;
+        for scramble in ["R U", "R2 L2", "F U2 R' D"] {
+            let c = Alg::try_from(scramble).unwrap().to_cube();
+            let mut expected = vec![];
+            brute_force(&c, &mut vec![], 6, &mut expected);
+            expected.sort_by(|a, b| (a.len(), a.moves()).cmp(&(b.len(), b.moves())));
+            assert_eq!(solver.enumerate(&c, 6), Ok(expected));
+        }
+
+        // A target length does not cut the enumeration short
+        let c = Alg::try_from("R U").unwrap().to_cube();
+        let all = solver.enumerate(&c, 6).unwrap();
+        solver.limits.target_len = Some(6);
+        assert_eq!(solver.enumerate(&c, 6), Ok(all));
+    }
+
+    #[test]
+    #[ignore = "building the FlipSlice+CO table takes minutes"]
+    fn test_flip_slice_twist() {
+        let options = TableOptions {
+            phase1: Phase1Pruning::FlipSliceTwist,
+            ..TableOptions::default()
+        };
+        let solver = TwoPhaseSolver::with_tables(Metric::Htm, options);
+        let split = TwoPhaseSolver::new();
+        let scramble =
+            Alg::try_from("B2 R2 D L2 F2 L2 U2 B2 D L2 D' F2 U' B' R U L' B' D R D' L2 B' U'")
+                .unwrap();
+        let mut c = Cube::default();
+        for m in scramble.moves() {
+            c = c.apply_move(*m);
+            assert!(solver.eval_drud(&c) >= split.eval_drud(&c));
+        }
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+    }
+
+    #[test]
+    #[ignore = "building the CP+EP table takes minutes"]
+    fn test_corner_edge() {
+        let options = TableOptions {
+            phase2: Phase2Pruning::CornerEdge,
+            ..TableOptions::default()
+        };
+        let solver = TwoPhaseSolver::with_tables(Metric::Htm, options);
+        let split = TwoPhaseSolver::new();
+        let scramble = Alg::try_from("U R2 D' F2 L2 U2 B2 D R2 U' L2 F2 U B2 D2").unwrap();
+        let mut c = Cube::default();
+        for m in scramble.moves() {
+            c = c.apply_move(*m);
+            assert!(solver.eval_fin(&c) >= split.eval_fin(&c));
+        }
+        let sol = solver.solve(&c).unwrap();
+        assert!(sol.apply(&c).is_solved());
+    }
+
+    #[test]
+    fn test_solve_axes() {
+        let solver = TwoPhaseSolver::new();
+
+        // In DR on RL and FB only, found from the conjugated cubes without leaving DR
+        for scramble in ["R U2 L' F2 D2 R'", "F R2 B' U2 L2 F' D2"] {
+            let c = Alg::try_from(scramble).unwrap().to_cube();
+            let sol = solver.solve(&c).unwrap();
+            assert!(sol.apply(&c).is_solved());
+            assert!(sol.len() <= Alg::try_from(scramble).unwrap().len());
+        }
+    }
+}
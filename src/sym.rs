@@ -26,36 +26,89 @@ impl Sym {
     pub const DF2: Self = Self(14);
     pub const DL2: Self = Self(15);
 
+    pub const fn all() -> &'static [Sym] {
+        &[
+            Self::UF,
+            Self::UR,
+            Self::UB,
+            Self::UL,
+            Self::DB,
+            Self::DR,
+            Self::DF,
+            Self::DL,
+            Self::UF2,
+            Self::UR2,
+            Self::UB2,
+            Self::UL2,
+            Self::DB2,
+            Self::DR2,
+            Self::DF2,
+            Self::DL2,
+        ]
+    }
+
     // Symmetry via x2 rotation.
+    #[cfg(test)]
     const X2: Cube = Cube::from_repr(0x000, 0x0000, 0x89ab30127456, 0x01234567);
 
     // Symmetry via y2 rotation.
+    #[cfg(test)]
     const Y2: Cube = Cube::from_repr(0x000, 0x0000, 0x98ba54761032, 0x54761032);
 
-    // Symmetry via y rotation, not suitable for EO.
+    // Symmetry via y rotation, without the EO change of the E-slice edges.
+    #[cfg(test)]
     const Y: Cube = Cube::from_repr(0x000, 0x0000, 0x8ba947650321, 0x47650321);
 
     // Symmetry via mirror across the M slice, not suitable for CO.
+    #[cfg(test)]
     const LR: Cube = Cube::from_repr(0x000, 0x0000, 0xab8956741230, 0x67452301);
 
+    // Rotation moving the FB axis onto UD, so that F turns become U turns under conjugation. This
+    // changes the axes orientation is measured against, so unlike the DRUD-preserving
+    // conjugators it carries EO and CO.
+    pub const FB_TO_UD: Cube = Cube::from_repr(0x0055, 0x6699, 0x3157b2a68094, 0x32670154);
+
+    // Rotation moving the RL axis onto UD, so that R turns become U turns under conjugation.
+    pub const RL_TO_UD: Cube = Cube::from_repr(0x0fff, 0x9966, 0x62045a197b38, 0x62157304);
+
+    /// Conjugator which moves the given axis onto UD.
+    pub fn axis_conjugator(axis: Axis) -> Cube {
+        match axis {
+            Axis::UD => Cube::default(),
+            Axis::FB => Self::FB_TO_UD,
+            Axis::RL => Self::RL_TO_UD,
+        }
+    }
+
+    /// Conjugate the cube so that the given axis becomes UD, allowing it to be reduced to DR on
+    /// that axis by searching for DRUD.
+    pub fn conj_axis(axis: Axis, c: &Cube) -> Cube {
+        let s = Self::axis_conjugator(axis);
+        s.compose(c).compose(&s.inverse())
+    }
+
+    /// The conjugators of symmetries with an odd number of y rotations flip the E-slice edges,
+    /// since the E-slice edges are oriented relative to FB but the rotation takes FB onto RL.
+    /// Mirrors reverse the direction of corner twists, which cannot be represented in the
+    /// conjugator, so conjugation negates CO for them instead.
     pub fn conjugator(self) -> Cube {
         match self.0 {
             0 => Cube::from_repr(0x0000, 0x0000, 0xba9876543210, 0x76543210),
-            1 => Cube::from_repr(0x0000, 0x0000, 0x8ba947650321, 0x47650321),
+            1 => Cube::from_repr(0x0f00, 0x0000, 0x8ba947650321, 0x47650321),
             2 => Cube::from_repr(0x0000, 0x0000, 0x98ba54761032, 0x54761032),
-            3 => Cube::from_repr(0x0000, 0x0000, 0xa98b65472103, 0x65472103),
+            3 => Cube::from_repr(0x0f00, 0x0000, 0xa98b65472103, 0x65472103),
             4 => Cube::from_repr(0x0000, 0x0000, 0x89ab30127456, 0x01234567),
-            5 => Cube::from_repr(0x0000, 0x0000, 0xb89a23016745, 0x30127456),
+            5 => Cube::from_repr(0x0f00, 0x0000, 0xb89a23016745, 0x30127456),
             6 => Cube::from_repr(0x0000, 0x0000, 0xab8912305674, 0x23016745),
-            7 => Cube::from_repr(0x0000, 0x0000, 0x9ab801234567, 0x12305674),
+            7 => Cube::from_repr(0x0f00, 0x0000, 0x9ab801234567, 0x12305674),
             8 => Cube::from_repr(0x0000, 0x0000, 0xab8956741230, 0x67452301),
-            9 => Cube::from_repr(0x0000, 0x0000, 0x9ab845670123, 0x56741230),
+            9 => Cube::from_repr(0x0f00, 0x0000, 0x9ab845670123, 0x56741230),
             10 => Cube::from_repr(0x0000, 0x0000, 0x89ab74563012, 0x45670123),
-            11 => Cube::from_repr(0x0000, 0x0000, 0xb89a67452301, 0x74563012),
+            11 => Cube::from_repr(0x0f00, 0x0000, 0xb89a67452301, 0x74563012),
             12 => Cube::from_repr(0x0000, 0x0000, 0x98ba10325476, 0x10325476),
-            13 => Cube::from_repr(0x0000, 0x0000, 0xa98b21036547, 0x21036547),
+            13 => Cube::from_repr(0x0f00, 0x0000, 0xa98b21036547, 0x21036547),
             14 => Cube::from_repr(0x0000, 0x0000, 0xba9832107654, 0x32107654),
-            15 => Cube::from_repr(0x0000, 0x0000, 0x8ba903214765, 0x03214765),
+            15 => Cube::from_repr(0x0f00, 0x0000, 0x8ba903214765, 0x03214765),
             _ => panic!("invalid symmetry coordinate: {}", self.0),
         }
     }
@@ -84,16 +137,33 @@ impl Sym {
         let s1 = s.inverse_corners();
         let c2 = s.compose_corners(c).compose_corners(&s1);
         Cube {
-            co: c2.co,
+            co: self.orient_corners(c2.co),
             cp: c2.cp,
             ..*c
         }
     }
 
+    /// Conjugate the cube by the symmetry, orientation included. Edges keep being oriented
+    /// against the FB axis, so the symmetries with an odd number of y rotations flip the
+    /// E-slice edges, and corners keep being oriented against UD, with mirrors reversing the
+    /// direction of their twists. Conjugating a move therefore gives a move exactly.
     pub fn conj(self, c: &Cube) -> Cube {
         let s = self.conjugator();
         let s1 = s.inverse();
-        s.compose(c).compose(&s1)
+        let c2 = s.compose(c).compose(&s1);
+        Cube {
+            co: self.orient_corners(c2.co),
+            ..c2
+        }
+    }
+
+    /// Whether the symmetry mirrors the cube.
+    pub fn is_mirror(self) -> bool {
+        self.0 & 8 != 0
+    }
+
+    fn orient_corners(self, co: CO) -> CO {
+        if self.is_mirror() { co.inverse() } else { co }
     }
 
     pub fn compose(self, rhs: Sym) -> Self {
@@ -131,6 +201,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_conj_axis() {
+        use Move::*;
+        assert_eq!(Sym::conj_axis(Axis::UD, &Cube::from(R)), Cube::from(R));
+        assert_eq!(Sym::conj_axis(Axis::FB, &Cube::from(F)), Cube::from(U));
+        assert_eq!(Sym::conj_axis(Axis::FB, &Cube::from(R2)), Cube::from(R2));
+        assert_eq!(Sym::conj_axis(Axis::RL, &Cube::from(R3)), Cube::from(U3));
+        assert_eq!(Sym::conj_axis(Axis::RL, &Cube::from(F)), Cube::from(F));
+
+        // Every move is conjugated onto a move, orientation included
+        for axis in [Axis::FB, Axis::RL] {
+            for m in Move::all() {
+                let c = Sym::conj_axis(axis, &Cube::from(*m));
+                assert!(Move::all().iter().any(|n| Cube::from(*n) == c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_conj_moves() {
+        // Conjugating a move gives another move exactly, orientation included
+        for coord in 0..16 {
+            let s = Sym::from_coord(coord);
+            for m in Move::all() {
+                let c = s.conj(&Cube::from(*m));
+                assert!(Move::all().iter().any(|n| Cube::from(*n) == c));
+                assert_eq!(s.conj_edges(&Cube::from(*m)).eo, c.eo);
+                assert_eq!(s.conj_corners(&Cube::from(*m)).co, c.co);
+            }
+        }
+        assert_eq!(Sym::UR.conj(&Cube::from(Move::F)), Cube::from(Move::R));
+        assert_eq!(Sym::UF2.conj(&Cube::from(Move::R)), Cube::from(Move::L3));
+    }
+
+    #[test]
+    fn test_conjugators() {
+        // The conjugators permute the pieces as the generators composed by the layout m|x|yy
+        let pow = |c: &Cube, n: usize| (0..n).fold(Cube::default(), |acc, _| acc.compose(c));
+        for coord in 0..16 {
+            let s = Sym::from_coord(coord);
+            let y = pow(&Sym::Y, coord & 3);
+            let x = pow(&Sym::X2, (coord >> 2) & 1);
+            let m = pow(&Sym::LR, coord >> 3);
+            let c = y.compose(&x).compose(&m);
+            assert_eq!(s.conjugator().ep, c.ep, "{coord}");
+            assert_eq!(s.conjugator().cp, c.cp, "{coord}");
+        }
+        assert_eq!(pow(&Sym::Y, 2), Sym::Y2);
+    }
+
     #[test]
     fn test_conj() {
         for coord in 0..16 {
@@ -0,0 +1,442 @@
+use std::sync::OnceLock;
+
+use crate::*;
+
+/// One of the 48 symmetries of the cube, the 24 rotations each with or without a mirror.
+///
+/// Layout: a * 16 + s
+/// - s: DRUD-preserving symmetry `Sym`, applied first
+/// - a: axis moved onto UD afterwards, 0 for UD, 1 for FB and 2 for RL
+///
+/// so the first 16 are the DRUD-preserving symmetries with the same coordinates as `Sym`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sym48(u8);
+
+/// Conjugators along with the multiplication and inverse tables, computed on first use.
+struct Tables {
+    conj: Vec<Cube>,
+    mult: Vec<[u8; 48]>,
+    inv: [u8; 48],
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let conj = (0..48).map(conjugator).collect::<Vec<_>>();
+        // Find each product by its action on a cube which no symmetry fixes
+        // R U2 F' L D B2 R' U F2 D' L2 B, built directly since parsing can use the tables
+        let probe = {
+            use Move::*;
+            Alg::from(vec![R, U2, F3, L, D, B2, R3, U, F2, D3, L2, B]).to_cube()
+        };
+        let images = (0..48)
+            .map(|i| Sym48(i).conj_with(&conj, &probe))
+            .collect::<Vec<_>>();
+        let mut mult = vec![[0; 48]; 48];
+        let mut inv = [0; 48];
+        for a in 0..48 {
+            for b in 0..48 {
+                let c = Sym48(a).conj_with(&conj, &images[b as usize]);
+                let ab = images.iter().position(|x| *x == c).unwrap() as u8;
+                mult[a as usize][b as usize] = ab;
+                if ab == 0 {
+                    inv[a as usize] = b;
+                }
+            }
+        }
+        Tables { conj, mult, inv }
+    })
+}
+
+/// Negate the CO of every corner, which is how mirrors act on corner twists.
+fn negate_co(c: &Cube) -> Cube {
+    Cube {
+        co: c.co.inverse(),
+        ..*c
+    }
+}
+
+/// Conjugator for the symmetry with the given coordinate, such that conjugating by it and then
+/// negating CO for mirrors first applies the DRUD-preserving symmetry and then moves the axis.
+fn conjugator(coord: u8) -> Cube {
+    let s = Sym::from_coord(coord as usize % 16);
+    let axis = match coord / 16 {
+        0 => Axis::UD,
+        1 => Axis::FB,
+        _ => Axis::RL,
+    };
+    let a = Sym::axis_conjugator(axis);
+    // Moving the axis after a mirror must itself be mirrored, for CO to be negated only once
+    let a = if s.is_mirror() { negate_co(&a) } else { a };
+    a.compose(&s.conjugator())
+}
+
+impl Sym48 {
+    /// All 48 symmetries, by coordinate.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..48).map(Self)
+    }
+
+    pub fn coord(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn from_coord(coord: usize) -> Self {
+        assert!(coord < 48, "invalid symmetry coordinate");
+        Self(coord as u8)
+    }
+
+    /// Cube to conjugate by, after which CO is negated for mirrors, see `conj`.
+    pub fn conjugator(self) -> Cube {
+        tables().conj[self.coord()].clone()
+    }
+
+    /// Whether the symmetry mirrors the cube.
+    pub fn is_mirror(self) -> bool {
+        self.0 % 16 >= 8
+    }
+
+    pub fn conj(self, c: &Cube) -> Cube {
+        self.conj_with(&tables().conj, c)
+    }
+
+    fn conj_with(self, conj: &[Cube], c: &Cube) -> Cube {
+        let s = &conj[self.coord()];
+        let c2 = s.compose(c).compose(&s.inverse());
+        if self.is_mirror() { negate_co(&c2) } else { c2 }
+    }
+
+    /// The symmetry which conjugates by `rhs` and then by `self`.
+    pub fn compose(self, rhs: Self) -> Self {
+        Self(tables().mult[self.coord()][rhs.coord()])
+    }
+
+    pub fn inverse(self) -> Self {
+        Self(tables().inv[self.coord()])
+    }
+
+    /// Split the symmetry into a DRUD-preserving symmetry followed by moving an axis onto UD,
+    /// so that conjugating by it is `Sym::conj_axis(axis, &s.conj(c))`.
+    pub fn decompose(self) -> (Axis, Sym) {
+        let axis = match self.0 / 16 {
+            0 => Axis::UD,
+            1 => Axis::FB,
+            _ => Axis::RL,
+        };
+        (axis, Sym::from_coord(self.coord() % 16))
+    }
+
+    /// The symmetry as a DRUD-preserving symmetry, if it is one.
+    pub fn to_drud(self) -> Option<Sym> {
+        match self.decompose() {
+            (Axis::UD, s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Kind of a rotation: 0 for the identity, 1 for a quarter turn about a face, 2 for a half
+    /// turn about a face, 3 for a turn about a corner and 4 for a half turn about an edge.
+    fn rotation_kind(self) -> usize {
+        let id = Self::from_coord(0);
+        let square = self.compose(self);
+        if self == id {
+            0
+        } else if square != id && square.compose(self) == id {
+            3
+        } else if square != id {
+            1
+        } else if Self::all().any(|x| !x.is_mirror() && x.compose(x) == self) {
+            2
+        } else {
+            4
+        }
+    }
+}
+
+impl From<Sym> for Sym48 {
+    fn from(s: Sym) -> Self {
+        Self(s.coord() as u8)
+    }
+}
+
+/// A set of symmetries and antisymmetries, where an antisymmetry of a cube conjugates it onto its
+/// inverse.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SymSet {
+    syms: u64,
+    antisyms: u64,
+}
+
+impl SymSet {
+    /// Every symmetry, without antisymmetries.
+    pub fn all() -> Self {
+        Self {
+            syms: (1 << 48) - 1,
+            antisyms: 0,
+        }
+    }
+
+    /// Every symmetry and antisymmetry.
+    pub fn all_with_antisyms() -> Self {
+        Self {
+            syms: (1 << 48) - 1,
+            antisyms: (1 << 48) - 1,
+        }
+    }
+
+    pub fn insert(&mut self, s: Sym48) {
+        self.syms |= 1 << s.coord();
+    }
+
+    pub fn insert_antisym(&mut self, s: Sym48) {
+        self.antisyms |= 1 << s.coord();
+    }
+
+    pub fn contains(&self, s: Sym48) -> bool {
+        self.syms >> s.coord() & 1 == 1
+    }
+
+    pub fn contains_antisym(&self, s: Sym48) -> bool {
+        self.antisyms >> s.coord() & 1 == 1
+    }
+
+    /// The symmetries in the set, by coordinate.
+    pub fn syms(&self) -> impl Iterator<Item = Sym48> {
+        let syms = self.syms;
+        Sym48::all().filter(move |s| syms >> s.coord() & 1 == 1)
+    }
+
+    /// The antisymmetries in the set, by coordinate.
+    pub fn antisyms(&self) -> impl Iterator<Item = Sym48> {
+        let antisyms = self.antisyms;
+        Sym48::all().filter(move |s| antisyms >> s.coord() & 1 == 1)
+    }
+
+    /// Number of symmetries and antisymmetries in the set.
+    pub fn len(&self) -> usize {
+        (self.syms.count_ones() + self.antisyms.count_ones()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether every symmetry in the set is a rotation.
+    pub fn is_chiral(&self) -> bool {
+        self.syms().chain(self.antisyms()).all(|s| !s.is_mirror())
+    }
+
+    /// The point group formed by the symmetries in the set, such as `Oh` for the symmetries of
+    /// superflip or `S6` for those of cube-in-cube, or `None` if they do not form a group.
+    /// Antisymmetries are ignored, and groups of the same type about different axes, like the
+    /// half turns about a face and about an edge, are not told apart.
+    pub fn class(&self) -> Option<SymClass> {
+        use SymClass::*;
+        let id = Sym48::from_coord(0);
+        let is_group = self.contains(id)
+            && self
+                .syms()
+                .all(|a| self.syms().all(|b| self.contains(a.compose(b))));
+        if !is_group {
+            return None;
+        }
+
+        // Count the rotations by their kind, and the mirrors by the kind of rotation they make
+        // with the inversion, which is the one mirror commuting with every symmetry
+        let inversion = Sym48::all()
+            .find(|m| m.is_mirror() && Sym48::all().all(|s| m.compose(s) == s.compose(*m)))
+            .unwrap();
+        let mut rotations = [0; 5];
+        let mut mirrors = [0; 5];
+        for s in self.syms() {
+            if s.is_mirror() {
+                mirrors[inversion.compose(s).rotation_kind()] += 1;
+            } else {
+                rotations[s.rotation_kind()] += 1;
+            }
+        }
+        let n_rotations = rotations.iter().sum::<usize>();
+        let (face_quarter, inv, rotoreflection) = (rotations[1] > 0, mirrors[0] > 0, mirrors[1]);
+        let class = match (
+            n_rotations,
+            face_quarter,
+            mirrors.iter().sum::<usize>() > 0,
+            inv,
+        ) {
+            (24, _, false, _) => O,
+            (24, _, true, _) => Oh,
+            (12, _, false, _) => T,
+            (12, _, true, true) => Th,
+            (12, _, true, false) => Td,
+            (8, _, false, _) => D4,
+            (8, _, true, _) => D4h,
+            (6, _, false, _) => D3,
+            (6, _, true, _) => D3d,
+            (4, true, false, _) => C4,
+            (4, true, true, true) => C4h,
+            (4, true, true, false) => C4v,
+            (4, false, false, _) => D2,
+            (4, false, true, true) => D2h,
+            (4, false, true, false) => D2d,
+            (3, _, false, _) => C3,
+            (3, _, true, true) => S6,
+            (3, _, true, false) => C3v,
+            (2, _, false, _) => C2,
+            (2, _, true, true) => C2h,
+            (2, _, true, false) if rotoreflection > 0 => S4,
+            (2, _, true, false) => C2v,
+            (1, _, false, _) => C1,
+            (1, _, true, true) => Ci,
+            (1, _, true, false) => Cs,
+            _ => unreachable!("not a subgroup of Oh"),
+        };
+        Some(class)
+    }
+}
+
+/// Type of a group of symmetries of the cube, as a point group in Schoenflies notation, see
+/// `SymSet::class`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymClass {
+    Oh,
+    O,
+    Td,
+    Th,
+    T,
+    D4h,
+    D4,
+    C4v,
+    C4h,
+    C4,
+    S4,
+    D2d,
+    D3d,
+    D3,
+    C3v,
+    S6,
+    C3,
+    D2h,
+    D2,
+    C2v,
+    C2h,
+    C2,
+    Cs,
+    Ci,
+    C1,
+}
+
+/// A cube compared and hashed up to symmetry, so that the conjugates of a cube by every symmetry
+/// in a set are equal. Only cubes made with the same set should be compared.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UpToSym(Cube);
+
+impl UpToSym {
+    /// The cube up to every symmetry.
+    pub fn new(c: &Cube) -> Self {
+        Self::with_syms(c, SymSet::all())
+    }
+
+    /// The cube up to the symmetries in the set, which should form a group.
+    pub fn with_syms(c: &Cube, syms: SymSet) -> Self {
+        Self(c.canonical_under(syms).0)
+    }
+
+    /// The representative of the cube's conjugates, see `Cube::canonical_under`.
+    pub fn canonical(&self) -> &Cube {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn test_cube() -> Cube {
+        Alg::try_from("F2 U' R2 B L' D2 F R' U2 B' L U")
+            .unwrap()
+            .to_cube()
+    }
+
+    #[test]
+    fn test_group_axioms() {
+        let id = Sym48::from_coord(0);
+        for a in Sym48::all() {
+            assert_eq!(id.compose(a), a);
+            assert_eq!(a.compose(id), a);
+            assert_eq!(a.compose(a.inverse()), id);
+            assert_eq!(a.inverse().compose(a), id);
+            for b in Sym48::all() {
+                for c in Sym48::all() {
+                    assert_eq!(a.compose(b).compose(c), a.compose(b.compose(c)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conj() {
+        let c = test_cube();
+        let images = Sym48::all().map(|s| s.conj(&c)).collect::<Vec<_>>();
+        for (i, x) in images.iter().enumerate() {
+            assert!(!images[..i].contains(x));
+        }
+        for a in Sym48::all() {
+            assert!(a.conj(&Cube::default()).is_solved());
+            for b in Sym48::all() {
+                assert_eq!(a.compose(b).conj(&c), a.conj(&b.conj(&c)));
+            }
+            // Every move is conjugated onto a move, orientation included
+            for m in Move::all() {
+                let x = a.conj(&Cube::from(*m));
+                assert!(Move::all().iter().any(|n| Cube::from(*n) == x));
+            }
+        }
+        assert_eq!(Sym48::all().filter(|s| s.is_mirror()).count(), 24);
+    }
+
+    #[test]
+    fn test_class() {
+        // Every kind of rotation and reflection is counted correctly
+        assert_eq!(SymSet::all().class(), Some(SymClass::Oh));
+        let mut rotations = SymSet::default();
+        Sym48::all()
+            .filter(|s| !s.is_mirror())
+            .for_each(|s| rotations.insert(s));
+        assert_eq!(rotations.class(), Some(SymClass::O));
+        let mut kinds = [0; 5];
+        rotations.syms().for_each(|s| kinds[s.rotation_kind()] += 1);
+        assert_eq!(kinds, [1, 6, 3, 8, 6]);
+        let mut drud = SymSet::default();
+        (0..16).for_each(|i| drud.insert(Sym48::from_coord(i)));
+        assert_eq!(drud.class(), Some(SymClass::D4h));
+
+        let mut set = SymSet::default();
+        set.insert(Sym48::from_coord(0));
+        assert_eq!(set.class(), Some(SymClass::C1));
+        set.insert(Sym48::from(Sym::UF2));
+        assert_eq!(set.class(), Some(SymClass::Cs));
+        set.insert(Sym48::from(Sym::UR));
+        assert_eq!(set.class(), None);
+    }
+
+    #[test]
+    fn test_drud() {
+        let c = test_cube();
+        assert_eq!(Sym48::all().filter(|s| s.to_drud().is_some()).count(), 16);
+        for coord in 0..16 {
+            let s = Sym::from_coord(coord);
+            assert_eq!(Sym48::from(s).conj(&c), s.conj(&c));
+            assert_eq!(Sym48::from(s).to_drud().unwrap().coord(), coord);
+            for t in (0..16).map(Sym::from_coord) {
+                assert_eq!(
+                    Sym48::from(s.compose(t)),
+                    Sym48::from(s).compose(Sym48::from(t))
+                );
+            }
+        }
+        for a in Sym48::all() {
+            let (axis, s) = a.decompose();
+            assert_eq!(a.conj(&c), Sym::conj_axis(axis, &s.conj(&c)));
+        }
+    }
+}
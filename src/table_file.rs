@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::*;
+
+/// Version of the table file format, to be bumped whenever the layout of any table changes.
+const VERSION: u32 = 2;
+
+const MAGIC: [u8; 4] = *b"DRTB";
+
+/// Identifies the contents of a table file, so that a file written for a different table, or by
+/// an older version of the format, is rejected instead of loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TableHeader {
+    /// Names of the coordinates and storage making up the table.
+    pub names: Vec<&'static str>,
+    /// Number of values of each coordinate.
+    pub n_values: Vec<usize>,
+    /// Fingerprint of whatever else the table depends on, like the moveset.
+    pub fingerprint: u64,
+}
+
+/// 64-bit FNV-1a hash.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
+
+/// Fingerprint of the moves and metric a table was built with, and the symmetries it is reduced
+/// by, including whether it is reduced by inversion.
+pub(crate) fn moveset_fingerprint(
+    moveset: &[Move],
+    metric: Metric,
+    syms: &[Sym],
+    antisym: bool,
+) -> u64 {
+    let mut bytes: Vec<u8> = moveset.iter().map(|m| *m as u8).collect();
+    bytes.push(0xff);
+    bytes.push(metric as u8);
+    bytes.push(0xff);
+    bytes.extend(syms.iter().map(|s| s.coord() as u8));
+    bytes.push(antisym as u8);
+    fnv1a(&bytes)
+}
+
+impl TableHeader {
+    fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        let mut ret = MAGIC.to_vec();
+        ret.extend(VERSION.to_le_bytes());
+        ret.extend((self.names.len() as u32).to_le_bytes());
+        for name in &self.names {
+            ret.extend((name.len() as u32).to_le_bytes());
+            ret.extend(name.as_bytes());
+        }
+        ret.extend((self.n_values.len() as u32).to_le_bytes());
+        for n in &self.n_values {
+            ret.extend((*n as u64).to_le_bytes());
+        }
+        ret.extend(self.fingerprint.to_le_bytes());
+        ret.extend((payload.len() as u64).to_le_bytes());
+        ret.extend(fnv1a(payload).to_le_bytes());
+        ret
+    }
+}
+
+/// Write the header and payload to the file, replacing it only once the whole table is written.
+/// The table is first written to a temporary file unique to the process and call, so that
+/// concurrent writers never rename each other's partial files into place.
+pub(crate) fn write_table(path: &Path, header: &TableHeader, payload: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut bytes = header.encode(payload);
+    bytes.extend(payload);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_extension(format!("{}.{n}.tmp", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Read the payload of a table file, see `parse_table`.
+pub(crate) fn read_table(path: &Path, header: &TableHeader) -> io::Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    parse_table(&bytes, header).map(<[u8]>::to_vec)
+}
+
+/// The payload of the contents of a table file, failing with `InvalidData` if the file does not
+/// have the expected header or its payload does not match the checksum.
+pub(crate) fn parse_table<'a>(bytes: &'a [u8], header: &TableHeader) -> io::Result<&'a [u8]> {
+    // The payload length and checksum are the last 16 bytes of the header
+    let expected = header.encode(&[]);
+    let n = expected.len();
+    if bytes.len() < n || bytes[..n - 16] != expected[..n - 16] {
+        return Err(invalid("stale table file"));
+    }
+    let len = u64::from_le_bytes(bytes[n - 16..n - 8].try_into().unwrap());
+    let checksum = u64::from_le_bytes(bytes[n - 8..n].try_into().unwrap());
+    let payload = &bytes[n..];
+    if payload.len() as u64 != len || fnv1a(payload) != checksum {
+        return Err(invalid("corrupt table file"));
+    }
+    Ok(payload)
+}
+
+pub(crate) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Little-endian reader over a table payload.
+pub(crate) struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(invalid("truncated table file"));
+        }
+        let (a, b) = self.0.split_at(n);
+        self.0 = b;
+        Ok(a)
+    }
+
+    pub fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_table() {
+        let path = std::env::temp_dir().join(format!("table_file_{}.bin", std::process::id()));
+        let header = TableHeader {
+            names: vec!["A", "B"],
+            n_values: vec![3, 4],
+            fingerprint: 5,
+        };
+        write_table(&path, &header, &[1, 2, 3]).unwrap();
+        assert_eq!(read_table(&path, &header).unwrap(), vec![1, 2, 3]);
+
+        // Any difference in the header makes the file stale
+        let other = TableHeader {
+            fingerprint: 6,
+            ..header.clone()
+        };
+        assert_eq!(
+            read_table(&path, &other).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+
+        // A flipped bit in the payload fails the checksum
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(
+            read_table(&path, &header).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_writes() {
+        // Writers racing on the same table each replace it with a whole file
+        let path = std::env::temp_dir().join(format!("table_race_{}.bin", std::process::id()));
+        let header = TableHeader {
+            names: vec!["A"],
+            n_values: vec![3],
+            fingerprint: 5,
+        };
+        let payload = vec![7; 1 << 16];
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..8 {
+                        write_table(&path, &header, &payload).unwrap();
+                        assert_eq!(read_table(&path, &header).unwrap(), payload);
+                    }
+                });
+            }
+        });
+        fs::remove_file(&path).unwrap();
+    }
+}